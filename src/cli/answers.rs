@@ -0,0 +1,173 @@
+use super::prompts::prompt_variable;
+use crate::error::ProjectError;
+use crate::templating::{TemplateManifest, VariableSpec};
+use log::{debug, error};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+// Where answers come from, in order of precedence: `--var key=value`, the
+// `--answers` file, then a prompt (or the default with `--defaults` or
+// `--no-input`). A `[variables]` entry in the config replaces the default
+// declared in the manifest.
+pub struct AnswerSource {
+    vars: Map<String, Value>,
+    file: Map<String, Value>,
+    defaults: Map<String, Value>,
+    interactive: bool,
+    use_defaults: bool,
+}
+
+impl AnswerSource {
+    pub fn new(interactive: bool, use_defaults: bool) -> Self {
+        Self {
+            vars: Map::new(),
+            file: Map::new(),
+            defaults: Map::new(),
+            interactive,
+            use_defaults,
+        }
+    }
+
+    pub fn with_vars<S: AsRef<str>>(mut self, vars: &[S]) -> Result<Self, ProjectError> {
+        for var in vars {
+            let (key, value) = var.as_ref().split_once('=').ok_or_else(|| {
+                ProjectError::Config(format!(
+                    "Invalid --var '{}': expected key=value",
+                    var.as_ref()
+                ))
+            })?;
+            self.vars
+                .insert(key.trim().to_string(), Value::String(value.to_string()));
+        }
+        Ok(self)
+    }
+
+    pub fn with_answers_file<P: AsRef<Path>>(mut self, path: P) -> Result<Self, ProjectError> {
+        let path = path.as_ref();
+        debug!("Loading answers from: {:?}", path);
+        let content = fs::read_to_string(path).map_err(|e| {
+            error!("Failed to read answers file '{}': {}", path.display(), e);
+            ProjectError::Config(format!(
+                "Failed to read answers file '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let parsed = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str::<Value>(&content).map_err(|e| e.to_string())
+        } else {
+            toml::from_str::<toml::Table>(&content)
+                .map_err(|e| e.to_string())
+                .and_then(|table| serde_json::to_value(table).map_err(|e| e.to_string()))
+        };
+        match parsed {
+            Ok(Value::Object(answers)) => {
+                self.file = answers;
+                Ok(self)
+            }
+            Ok(_) => Err(ProjectError::Config(format!(
+                "Answers file '{}' must contain a table of answers",
+                path.display()
+            ))),
+            Err(e) => Err(ProjectError::Config(format!(
+                "Failed to parse answers file '{}': {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    pub fn with_defaults(
+        mut self,
+        defaults: &BTreeMap<String, toml::Value>,
+    ) -> Result<Self, ProjectError> {
+        for (key, value) in defaults {
+            let value = serde_json::to_value(value).map_err(|e| {
+                ProjectError::Config(format!("Invalid default for '{}': {}", key, e))
+            })?;
+            self.defaults.insert(key.clone(), value);
+        }
+        Ok(self)
+    }
+
+    pub fn is_interactive(&self) -> bool {
+        self.interactive
+    }
+
+    pub fn preset(&self, key: &str) -> Option<&Value> {
+        self.vars.get(key).or_else(|| self.file.get(key))
+    }
+
+    pub fn preset_str(&self, key: &str) -> Option<String> {
+        self.preset(key).map(|value| match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+    }
+
+    // Variables that cannot be answered without prompting
+    pub fn missing(&self, manifest: &TemplateManifest) -> Vec<String> {
+        if self.interactive {
+            return Vec::new();
+        }
+        manifest
+            .variables
+            .iter()
+            .filter(|spec| {
+                self.preset(&spec.name).is_none()
+                    && spec.default.is_none()
+                    && !self.defaults.contains_key(&spec.name)
+            })
+            .map(|spec| spec.name.clone())
+            .collect()
+    }
+
+    pub fn answer(
+        &self,
+        spec: &VariableSpec,
+        default: Option<Value>,
+    ) -> Result<Value, ProjectError> {
+        if let Some(value) = self.preset(&spec.name) {
+            return coerce(spec, value);
+        }
+        let default = match self.defaults.get(&spec.name) {
+            Some(value) => Some(coerce(spec, value)?),
+            None => default,
+        };
+        match default {
+            Some(default) if self.use_defaults || !self.interactive => Ok(default),
+            _ if self.interactive => prompt_variable(spec, default),
+            _ => Err(ProjectError::MissingInput(vec![spec.name.clone()])),
+        }
+    }
+
+    // Answers that don't correspond to a declared variable are passed through
+    // to the templates as-is.
+    pub fn resolve(
+        &self,
+        manifest: &TemplateManifest,
+        base: &Value,
+        reserved: &[&str],
+    ) -> Result<Map<String, Value>, ProjectError> {
+        let mut variables = manifest.resolve(base, |spec, default| self.answer(spec, default))?;
+        let answers = self.defaults.iter().chain(&self.file).chain(&self.vars);
+        for (key, value) in answers {
+            if !reserved.contains(&key.as_str())
+                && !manifest.variables.iter().any(|spec| &spec.name == key)
+            {
+                variables.insert(key.clone(), value.clone());
+            }
+        }
+        Ok(variables)
+    }
+}
+
+fn coerce(spec: &VariableSpec, value: &Value) -> Result<Value, ProjectError> {
+    let result = match value {
+        Value::String(raw) => spec.parse_value(raw),
+        other => spec.validate(other).map(|_| other.clone()),
+    };
+    result.map_err(|e| ProjectError::Config(format!("Invalid value for '{}': {}", spec.name, e)))
+}
@@ -1,12 +1,26 @@
-use crate::config::Config;
+use super::answers::AnswerSource;
+use super::prompts::prompt_conflict;
+use crate::config::{Config, ConfigLayers};
+use crate::core::conflicts::{resolve_conflicts, ConflictPolicy, ConflictReport};
+use crate::core::executor::Executor;
 use crate::core::project::Project;
 use crate::core::RuntimeContext;
 use crate::error::ProjectError;
+use crate::languages::python;
+use crate::languages::python::env::{download_command, PackageSources, ENV_BACKENDS};
 use crate::languages::{get_language, supported_languages};
-use clap::{Arg, Command};
+use clap::builder::PossibleValuesParser;
+use clap::{Arg, ArgAction, ArgMatches, Command};
 use dialoguer::{Input, Select};
 use log::{debug, error, info};
-use std::path::PathBuf;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const DEFAULT_WHEELHOUSE: &str = "wheelhouse";
+
+// Answer keys that select the generator rather than feed the templates
+const RESERVED_ANSWERS: &[&str] = &["language", "project_type", "name"];
 
 pub async fn run() -> Result<(), ProjectError> {
     debug!("Parsing command line arguments");
@@ -25,56 +39,328 @@ pub async fn run() -> Result<(), ProjectError> {
                 .required(false),
         )
         .arg(Arg::new("name").help("Project name").required(false))
+        .arg(
+            Arg::new("output_dir")
+                .short('o')
+                .long("output-dir")
+                .value_name("DIR")
+                .help("Directory to generate the project in [default: ./<name>]"),
+        )
         .arg(
             Arg::new("config")
                 .short('c')
                 .long("config")
                 .value_name("FILE")
-                .help("Custom configuration file"),
+                .global(true)
+                .help("Configuration file applied over the other layers"),
+        )
+        .arg(
+            Arg::new("set")
+                .long("set")
+                .value_name("KEY=VALUE")
+                .action(ArgAction::Append)
+                .global(true)
+                .help("Override a configuration key, e.g. general.version=3.12"),
+        )
+        .arg(
+            Arg::new("env")
+                .long("env")
+                .value_name("BACKEND")
+                .value_parser(PossibleValuesParser::new(ENV_BACKENDS))
+                .global(true)
+                .help("Python environment backend (sets settings.env)"),
+        )
+        .arg(
+            Arg::new("find_links")
+                .long("find-links")
+                .value_name("DIR")
+                .global(true)
+                .help("Install Python packages from this wheel directory (sets settings.find_links)"),
+        )
+        .arg(
+            Arg::new("index_url")
+                .long("index-url")
+                .value_name("URL")
+                .global(true)
+                .help("Install Python packages from this index (sets settings.index_url)"),
+        )
+        .arg(
+            Arg::new("constraints")
+                .long("constraints")
+                .value_name("FILE")
+                .global(true)
+                .help("Limit Python package versions with this pip constraints file (sets settings.constraints)"),
+        )
+        .arg(
+            Arg::new("pin")
+                .long("pin")
+                .action(ArgAction::SetTrue)
+                .help("Pin Python dependencies to the versions currently available (sets settings.pin)"),
+        )
+        .arg(
+            Arg::new("templates_dir")
+                .short('t')
+                .long("templates-dir")
+                .value_name("DIR")
+                .action(ArgAction::Append)
+                .help("Directory searched for templates before the built-in ones"),
+        )
+        .arg(
+            Arg::new("var")
+                .long("var")
+                .value_name("KEY=VALUE")
+                .action(ArgAction::Append)
+                .help("Answer a template variable without prompting"),
+        )
+        .arg(
+            Arg::new("answers")
+                .long("answers")
+                .value_name("FILE")
+                .help("TOML or JSON file with answers to template variables"),
+        )
+        .arg(
+            Arg::new("defaults")
+                .long("defaults")
+                .action(ArgAction::SetTrue)
+                .help("Accept declared defaults instead of prompting for them"),
+        )
+        .arg(
+            Arg::new("no_input")
+                .long("no-input")
+                .action(ArgAction::SetTrue)
+                .help("Never prompt; fail if an answer is missing"),
+        )
+        .arg(
+            Arg::new("dry_run")
+                .short('n')
+                .long("dry-run")
+                .action(ArgAction::SetTrue)
+                .help("Print the files and commands without touching disk"),
+        )
+        .arg(
+            Arg::new("keep_on_failure")
+                .long("keep-on-failure")
+                .action(ArgAction::SetTrue)
+                .help("Leave partially generated files in place if generation fails"),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .action(ArgAction::SetTrue)
+                .help("Show the output of setup commands as they run"),
+        )
+        .arg(
+            Arg::new("timeout")
+                .long("timeout")
+                .value_name("SECONDS")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .help("Kill setup commands that run longer than this"),
+        )
+        .arg(
+            Arg::new("on_conflict")
+                .long("on-conflict")
+                .value_name("POLICY")
+                .value_parser(["abort", "overwrite", "skip", "prompt"])
+                .conflicts_with_all(["force", "skip_existing"])
+                .help("What to do with files that already exist [default: abort]"),
+        )
+        .arg(
+            Arg::new("force")
+                .short('f')
+                .long("force")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("skip_existing")
+                .help("Overwrite existing files (same as --on-conflict overwrite)"),
+        )
+        .arg(
+            Arg::new("skip_existing")
+                .long("skip-existing")
+                .action(ArgAction::SetTrue)
+                .help("Keep existing files (same as --on-conflict skip)"),
+        )
+        .subcommand(
+            Command::new("config")
+                .about("Inspect the configuration")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("show")
+                        .about("Print the effective value of every configuration key")
+                        .arg(Arg::new("language").help("Language whose configuration to show"))
+                        .arg(
+                            Arg::new("origin")
+                                .long("origin")
+                                .action(ArgAction::SetTrue)
+                                .help("Also print the layer each value comes from"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("validate")
+                        .about("Check the configuration and report every problem")
+                        .arg(Arg::new("language").help("Language whose configuration to check")),
+                ),
+        )
+        .subcommand(
+            Command::new("wheelhouse")
+                .about("Manage a local directory of Python packages for offline installs")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("fetch")
+                        .about("Download the packages a Python project type installs")
+                        .arg(
+                            Arg::new("project_type")
+                                .required(true)
+                                .help("Project type whose dependencies to download"),
+                        )
+                        .arg(
+                            Arg::new("dest")
+                                .long("dest")
+                                .value_name("DIR")
+                                .help("Where to put the packages [default: settings.find_links, or ./wheelhouse]"),
+                        ),
+                ),
         )
         .get_matches();
 
-    let config_path = matches
-        .get_one::<String>("config")
-        .map(|s| s.as_str())
-        .unwrap_or("config/python/default.toml");
-    let config = Config::load(config_path).map_err(|e| {
-        error!("Failed to load configuration: {}", e);
-        ProjectError::Config(format!("Failed to load configuration: {}", e))
-    })?;
+    let layers = config_layers(&matches)?;
+    if let Some(("config", config_matches)) = matches.subcommand() {
+        return config_command(config_matches, &layers);
+    }
+    if let Some(("wheelhouse", wheelhouse_matches)) = matches.subcommand() {
+        return wheelhouse_command(wheelhouse_matches, &layers);
+    }
+
+    let answers = answer_source(&matches)?;
+    let mut missing = Vec::new();
 
     let language_options = supported_languages();
-    let language_name = match matches.get_one::<String>("language") {
-        Some(lang) => lang.to_string(),
-        None => {
+    let language_name = match matches
+        .get_one::<String>("language")
+        .cloned()
+        .or_else(|| answers.preset_str("language"))
+        .or_else(|| layers.language())
+    {
+        Some(lang) => Some(lang),
+        None if answers.is_interactive() => {
             let selection = Select::new()
                 .with_prompt("Select programming language")
                 .items(&language_options)
-                .interact()
-                .unwrap();
-            language_options[selection].to_string()
+                .interact()?;
+            Some(language_options[selection].to_string())
+        }
+        None => {
+            missing.push("language".to_string());
+            None
         }
     };
 
-    let project_type = match matches.get_one::<String>("project_type") {
-        Some(pt) => pt.to_string(),
-        None => {
-            let project_types = vec!["web", "cli", "data_science", "base"];
+    let language = match &language_name {
+        Some(name) => Some(
+            get_language(name)
+                .ok_or_else(|| ProjectError::Config(format!("Unsupported language: {}", name)))?,
+        ),
+        None => None,
+    };
+
+    // A `--config` file written for one language can't configure another
+    if let (Some(path), Some(language)) = (matches.get_one::<String>("config"), &language) {
+        let file_language = ConfigLayers::new()
+            .with_file("cli", Path::new(path))?
+            .language();
+        if let Some(file_language) = file_language {
+            if get_language(&file_language).map(|l| l.name()) != Some(language.name()) {
+                return Err(ProjectError::Config(format!(
+                    "Config file '{}' is for {} projects, not {}",
+                    path,
+                    file_language,
+                    language.name()
+                )));
+            }
+        }
+    }
+    let mut config = match &language {
+        Some(language) => {
+            let mut resolved = layers.resolve(language.name())?;
+            if let Some(dirs) = matches.get_many::<String>("templates_dir") {
+                resolved.prepend_templates_dirs(dirs.map(PathBuf::from).collect());
+            }
+            resolved.validate()?;
+            resolved.config
+        }
+        None => Config::new()?,
+    };
+    let answers = answers.with_defaults(&config.variables)?;
+
+    let preset_type = matches
+        .get_one::<String>("project_type")
+        .cloned()
+        .or_else(|| answers.preset_str("project_type"));
+    let project_type = match (preset_type, &language) {
+        (Some(pt), _) => Some(pt),
+        (None, Some(language)) if answers.is_interactive() => {
+            let project_types = language.project_types(&config);
             let selection = Select::new()
                 .with_prompt("Select project type")
                 .items(&project_types)
-                .interact()
-                .unwrap();
-            project_types[selection].to_string()
+                .interact()?;
+            Some(project_types[selection].to_string())
+        }
+        (None, _) => {
+            missing.push("project_type".to_string());
+            None
+        }
+    };
+
+    let output_dir = matches.get_one::<String>("output_dir").map(PathBuf::from);
+    let project_name = match matches
+        .get_one::<String>("name")
+        .cloned()
+        .or_else(|| answers.preset_str("name"))
+        .or_else(|| output_dir.as_deref().and_then(dir_name))
+    {
+        Some(name) => Some(name),
+        None if answers.is_interactive() => Some(
+            Input::<String>::new()
+                .with_prompt("Enter project name")
+                .interact()?,
+        ),
+        None => {
+            missing.push("name".to_string());
+            None
         }
     };
 
-    let project_name = match matches.get_one::<String>("name") {
-        Some(name) => name.to_string(),
-        None => Input::<String>::new()
-            .with_prompt("Enter project name")
-            .interact()
-            .unwrap(),
+    // Without a language and project type there is no manifest to check, so
+    // report what is known to be missing right away.
+    let (Some(language_name), Some(project_type), Some(language)) =
+        (language_name, project_type, language)
+    else {
+        return Err(ProjectError::MissingInput(missing));
+    };
+
+    // Both may run tools or reach the network (interpreters, pip against the
+    // index, the npm registry), which a dry run doesn't
+    let dry_run = matches.get_flag("dry_run");
+    let executor = Executor::new()
+        .keep_on_failure(matches.get_flag("keep_on_failure"))
+        .verbose(matches.get_flag("verbose"))
+        .timeout(
+            matches
+                .get_one::<u64>("timeout")
+                .map(|secs| Duration::from_secs(*secs)),
+        );
+    if !dry_run {
+        language.resolve_toolchain(&mut config)?;
+        language.resolve_dependencies(&mut config, &project_type, &executor)?;
+    }
+    let generator = language.get_generator(&config, &project_type)?;
+    debug!(
+        "Selected generator type: {}",
+        std::any::type_name_of_val(&generator)
+    );
+    missing.extend(answers.missing(generator.manifest()));
+    let Some(project_name) = project_name.filter(|_| missing.is_empty()) else {
+        return Err(ProjectError::MissingInput(missing));
     };
 
     info!(
@@ -83,47 +369,235 @@ pub async fn run() -> Result<(), ProjectError> {
     );
 
     // Create runtime context with user selections
-    let context = RuntimeContext::new(
+    let mut context = RuntimeContext::new(
         config,
         language_name.clone(),
         project_type.clone(),
         project_name.clone(),
     );
+    if let Some(dir) = output_dir {
+        context = context.with_path(dir);
+    }
 
-    let project = Project::new(&project_name, PathBuf::from(&project_name));
+    let project = Project::new(&project_name, context.project_path.clone());
     debug!("Project object created: {:?}", project.name);
 
-    let language = get_language(&language_name)
-        .ok_or_else(|| ProjectError::Config(format!("Unsupported language: {}", language_name)))?;
+    let base_context = generator.create_context(&project, &context.config);
+    let variables = answers.resolve(generator.manifest(), &base_context, RESERVED_ANSWERS)?;
+    let project = project.with_variables(variables);
 
-    let generator = language.get_generator(&context.config, &context.project_type);
-    debug!(
-        "Selected generator type: {}",
-        std::any::type_name_of_val(&generator)
+    info!("Planning project");
+    let mut plan = generator.plan(&project, &context.config).map_err(|e| {
+        error!("Failed to generate project: {}", e);
+        e
+    })?;
+    plan.extend(
+        language
+            .setup_environment(&project, &context.config)
+            .map_err(|e| {
+                error!("Failed to set up environment: {}", e);
+                e
+            })?,
     );
 
-    info!("Setting up environment");
-    match language.setup_environment(&project, &context.config) {
-        Ok(_) => {
-            info!("Generating project");
-            match generator.generate(&project, &context.config) {
-                Ok(_) => {
-                    info!("Project '{}' created successfully!", project_name);
-                    println!("To run your project:");
-                    println!("1. cd {}", project_name);
-                    println!("2. pip install -r requirements.txt");
-                    println!("3. python main.py");
-                }
-                Err(e) => {
-                    error!("Failed to generate project: {}", e);
-                    return Err(e);
+    let policy = conflict_policy(&matches, answers.is_interactive())?;
+    if dry_run {
+        // Only policies that don't need an answer are applied to the preview
+        if matches!(policy, ConflictPolicy::Skip | ConflictPolicy::Overwrite) {
+            resolve_conflicts(&mut plan, &project.path, policy, prompt_conflict)?;
+        }
+        println!("Dry run: nothing will be written or run.");
+        if let Some(note) = language.dry_run_note(&context.config) {
+            println!("{}", note);
+        }
+        print!("{}", plan.describe(&project.path));
+        return Ok(());
+    }
+    let report = resolve_conflicts(&mut plan, &project.path, policy, prompt_conflict)?;
+
+    executor.execute(&plan, &project.path).map_err(|e| {
+        error!("Failed to generate project: {}", e);
+        e
+    })?;
+    info!("Project '{}' created successfully!", project_name);
+    print_conflict_summary(&report);
+    println!("To run your project:");
+    for (i, step) in language
+        .next_steps(&project, &context.config)
+        .iter()
+        .enumerate()
+    {
+        println!("{}. {}", i + 1, step);
+    }
+    Ok(())
+}
+
+// Built-in config, `~/.config/poq/config.toml`, the nearest `.poq.toml`,
+// `POQ_*` env vars, then `--config` and `--set`.
+fn config_layers(matches: &ArgMatches) -> Result<ConfigLayers, ProjectError> {
+    let mut layers = ConfigLayers::discover(&std::env::current_dir()?)?.with_env(std::env::vars());
+    if let Some(path) = matches.get_one::<String>("config") {
+        layers = layers.with_file("cli", Path::new(path))?;
+    }
+    if let Some(env) = matches.get_one::<String>("env") {
+        layers = layers.with_flag("--env", "python.settings.env", env);
+    }
+    if let Some(dir) = matches.get_one::<String>("find_links") {
+        layers = layers.with_flag("--find-links", "python.settings.find_links", dir);
+    }
+    if let Some(url) = matches.get_one::<String>("index_url") {
+        layers = layers.with_flag("--index-url", "python.settings.index_url", url);
+    }
+    if let Some(file) = matches.get_one::<String>("constraints") {
+        layers = layers.with_flag("--constraints", "python.settings.constraints", file);
+    }
+    if matches.get_flag("pin") {
+        layers = layers.with_flag("--pin", "python.settings.pin", "true");
+    }
+    let overrides: Vec<&String> = matches
+        .get_many::<String>("set")
+        .map(|values| values.collect())
+        .unwrap_or_default();
+    layers.with_overrides(&overrides)
+}
+
+fn config_command(matches: &ArgMatches, layers: &ConfigLayers) -> Result<(), ProjectError> {
+    let Some((command, command_matches)) = matches.subcommand() else {
+        return Ok(());
+    };
+    let name = command_matches
+        .get_one::<String>("language")
+        .cloned()
+        .or_else(|| layers.language())
+        .unwrap_or_else(|| "python".to_string());
+    let language = get_language(&name)
+        .ok_or_else(|| ProjectError::Config(format!("Unsupported language: {}", name)))?;
+    let resolved = layers.resolve(language.name())?;
+    match command {
+        "show" => {
+            for (key, value, origin) in resolved.entries() {
+                if command_matches.get_flag("origin") {
+                    println!("{} = {}  # {}", key, value, origin);
+                } else {
+                    println!("{} = {}", key, value);
                 }
             }
         }
-        Err(e) => {
-            error!("Failed to set up environment: {}", e);
-            return Err(e);
+        "validate" => {
+            resolved.validate()?;
+            println!("The {} configuration is valid.", language.name());
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// Prompting needs a terminal, so a non-TTY stdin implies `--no-input`.
+fn answer_source(matches: &ArgMatches) -> Result<AnswerSource, ProjectError> {
+    let interactive = !matches.get_flag("no_input") && std::io::stdin().is_terminal();
+    let vars: Vec<&String> = matches
+        .get_many::<String>("var")
+        .map(|vars| vars.collect())
+        .unwrap_or_default();
+    let mut answers =
+        AnswerSource::new(interactive, matches.get_flag("defaults")).with_vars(&vars)?;
+    if let Some(path) = matches.get_one::<String>("answers") {
+        answers = answers.with_answers_file(path)?;
+    }
+    Ok(answers)
+}
+
+fn conflict_policy(
+    matches: &ArgMatches,
+    interactive: bool,
+) -> Result<ConflictPolicy, ProjectError> {
+    let policy = if matches.get_flag("force") {
+        ConflictPolicy::Overwrite
+    } else if matches.get_flag("skip_existing") {
+        ConflictPolicy::Skip
+    } else {
+        match matches.get_one::<String>("on_conflict") {
+            Some(policy) => policy.parse()?,
+            None => ConflictPolicy::Abort,
+        }
+    };
+    if policy == ConflictPolicy::Prompt && !interactive {
+        return Err(ProjectError::Config(
+            "--on-conflict prompt needs an interactive terminal".to_string(),
+        ));
+    }
+    Ok(policy)
+}
+
+fn print_conflict_summary(report: &ConflictReport) {
+    if !report.skipped.is_empty() {
+        println!("Kept {} existing file(s):", report.skipped.len());
+        for path in &report.skipped {
+            println!("  {}", path.display());
         }
     }
+    if !report.overwritten.is_empty() {
+        println!("Overwrote {} existing file(s):", report.overwritten.len());
+        for path in &report.overwritten {
+            println!("  {}", path.display());
+        }
+    }
+}
+
+// `-o .` names the project after the current directory
+fn dir_name(dir: &Path) -> Option<String> {
+    let dir = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    dir.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+// `wheelhouse fetch` runs on a connected machine; the directory is then
+// used through settings.find_links where there is no network.
+fn wheelhouse_command(matches: &ArgMatches, layers: &ConfigLayers) -> Result<(), ProjectError> {
+    let Some(("fetch", fetch_matches)) = matches.subcommand() else {
+        return Ok(());
+    };
+    let resolved = layers.resolve("python")?;
+    resolved.validate()?;
+    let config = resolved.config;
+    let project_type = fetch_matches
+        .get_one::<String>("project_type")
+        .map(String::as_str)
+        .unwrap_or_default();
+    let requirements =
+        python::generators::common::DependencyGroups::from_config(&config, project_type)?.all();
+    let sources = PackageSources::from_config(&config)?;
+    let dest = match fetch_matches.get_one::<String>("dest") {
+        Some(dir) => PathBuf::from(dir),
+        None => sources
+            .find_links
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_WHEELHOUSE)),
+    };
+    if requirements.is_empty() {
+        println!(
+            "The {} project type has no dependencies to download.",
+            project_type
+        );
+        return Ok(());
+    }
+
+    // The wheels have to suit the interpreter the projects will use
+    let interpreter = python::find_interpreter(&config)?;
+    Executor::new().verbose(true).run_command(
+        &download_command(
+            &interpreter.path.display().to_string(),
+            &requirements,
+            &sources,
+            &dest,
+        ),
+        &std::env::current_dir()?,
+    )?;
+    println!(
+        "Downloaded {} packages to {}; install from them with --find-links {}",
+        project_type,
+        dest.display(),
+        dest.display()
+    );
     Ok(())
 }
@@ -1 +1,3 @@
+pub mod answers;
 pub mod commands;
+pub mod prompts;
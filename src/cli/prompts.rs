@@ -0,0 +1,84 @@
+use crate::core::conflicts::{Conflict, Resolution};
+use crate::error::ProjectError;
+use crate::templating::{VariableSpec, VariableType};
+use dialoguer::{Confirm, Input, Select};
+use serde_json::Value;
+use similar::TextDiff;
+
+pub fn prompt_variable(spec: &VariableSpec, default: Option<Value>) -> Result<Value, ProjectError> {
+    let prompt = spec.prompt_text();
+    match spec.kind {
+        VariableType::Bool => {
+            let mut confirm = Confirm::new().with_prompt(prompt);
+            if let Some(Value::Bool(default)) = default {
+                confirm = confirm.default(default);
+            }
+            Ok(Value::Bool(confirm.interact()?))
+        }
+        VariableType::Choice => {
+            let default_index = default
+                .as_ref()
+                .and_then(Value::as_str)
+                .and_then(|d| spec.choices.iter().position(|c| c == d))
+                .unwrap_or(0);
+            let selection = Select::new()
+                .with_prompt(prompt)
+                .items(&spec.choices)
+                .default(default_index)
+                .interact()?;
+            Ok(Value::String(spec.choices[selection].clone()))
+        }
+        VariableType::String | VariableType::Int => {
+            let mut input = Input::<String>::new()
+                .with_prompt(prompt)
+                .allow_empty(spec.kind == VariableType::String)
+                .validate_with(|raw: &String| spec.parse_value(raw).map(|_| ()));
+            if let Some(default) = default {
+                let default = match default {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                if !default.is_empty() {
+                    input = input.default(default);
+                }
+            }
+            let raw = input.interact_text()?;
+            spec.parse_value(&raw).map_err(ProjectError::Config)
+        }
+    }
+}
+
+pub fn prompt_conflict(conflict: &Conflict) -> Result<Resolution, ProjectError> {
+    let path = conflict.path.display().to_string();
+    match (
+        std::str::from_utf8(&conflict.existing),
+        std::str::from_utf8(conflict.generated),
+    ) {
+        (Ok(existing), Ok(generated)) => {
+            let diff = TextDiff::from_lines(existing, generated);
+            println!(
+                "{}",
+                diff.unified_diff().context_radius(3).header(
+                    &format!("{} (existing)", path),
+                    &format!("{} (generated)", path)
+                )
+            );
+        }
+        _ => println!("Binary file {} differs", path),
+    }
+
+    let options = [
+        ("Overwrite", Resolution::Overwrite),
+        ("Keep existing", Resolution::Skip),
+        ("Overwrite all remaining", Resolution::OverwriteAll),
+        ("Keep all remaining", Resolution::SkipAll),
+        ("Abort", Resolution::Abort),
+    ];
+    let labels: Vec<&str> = options.iter().map(|(label, _)| *label).collect();
+    let selection = Select::new()
+        .with_prompt(format!("{} already exists", path))
+        .items(&labels)
+        .default(1)
+        .interact()?;
+    Ok(options[selection].1)
+}
@@ -0,0 +1,446 @@
+use super::validate::check_keys;
+use super::{builtin_source, Config, ConfigIssue, DEFAULT_CONFIG_FILE};
+use crate::error::ProjectError;
+use crate::languages::supported_languages;
+use crate::utils::user_config_dir;
+use log::debug;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const USER_CONFIG_FILE: &str = "config.toml";
+pub const PROJECT_CONFIG_FILE: &str = ".poq.toml";
+pub const ENV_PREFIX: &str = "POQ_";
+const ENV_SEPARATOR: &str = "__";
+
+// Where a layer came from; files keep their text to locate keys in
+#[derive(Clone)]
+struct Source {
+    origin: String,
+    file: Option<(String, String)>,
+}
+
+struct Layer {
+    source: Source,
+    table: toml::Table,
+    // Values from env vars and `--set` arrive as strings and take the type of
+    // the value they replace.
+    untyped: bool,
+}
+
+// Config sources in increasing precedence. Every layer is merged key by key
+// over the built-in config of the chosen language; a top-level table named
+// after a language (e.g. `[python.general]`) only applies to that language.
+#[derive(Default)]
+pub struct ConfigLayers {
+    layers: Vec<Layer>,
+}
+
+pub struct ResolvedConfig {
+    pub config: Config,
+    language: String,
+    sources: Vec<Source>,
+    // Leaf key to the index of the source that set it
+    origins: BTreeMap<String, usize>,
+    table: toml::Table,
+    // Leading `templates.dirs` entries that came from `--templates-dir`
+    cli_templates_dirs: usize,
+    // Unknown and mistyped keys, left out of `config`
+    key_issues: Vec<ConfigIssue>,
+}
+
+impl ConfigLayers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // `~/.config/poq/config.toml`, then the nearest `.poq.toml` from `cwd` up
+    pub fn discover(cwd: &Path) -> Result<Self, ProjectError> {
+        let mut layers = Self::new();
+        if let Some(path) = user_config_dir().map(|dir| dir.join(USER_CONFIG_FILE)) {
+            if path.is_file() {
+                layers = layers.with_file("user", &path)?;
+            }
+        }
+        if let Some(path) = find_project_config(cwd) {
+            layers = layers.with_file("project", &path)?;
+        }
+        Ok(layers)
+    }
+
+    pub fn with_file(mut self, kind: &str, path: &Path) -> Result<Self, ProjectError> {
+        debug!("Loading {} configuration from: {:?}", kind, path);
+        let content = fs::read_to_string(path).map_err(|e| {
+            ProjectError::Config(format!(
+                "Failed to read config file '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        let table = content.parse::<toml::Table>().map_err(|e| {
+            ProjectError::Config(format!(
+                "Failed to parse config file '{}': {}",
+                path.display(),
+                e
+            ))
+        })?;
+        self.layers.push(Layer {
+            source: Source {
+                origin: format!("{} ({})", kind, path.display()),
+                file: Some((path.display().to_string(), content)),
+            },
+            table,
+            untyped: false,
+        });
+        Ok(self)
+    }
+
+    // `POQ_GENERAL__VERSION=3.12` sets `general.version`; variables without
+    // a `__` (such as `POQ_TEMPLATES_DIR`) are not config keys.
+    pub fn with_env<I: IntoIterator<Item = (String, String)>>(mut self, vars: I) -> Self {
+        let mut vars: Vec<(String, String)> = vars
+            .into_iter()
+            .filter(|(name, _)| name.starts_with(ENV_PREFIX) && name.contains(ENV_SEPARATOR))
+            .collect();
+        vars.sort();
+        for (name, value) in vars {
+            let key: Vec<String> = name[ENV_PREFIX.len()..]
+                .split(ENV_SEPARATOR)
+                .map(str::to_lowercase)
+                .collect();
+            if key.iter().any(String::is_empty) {
+                continue;
+            }
+            self.push_value(format!("env ({})", name), &key, value);
+        }
+        self
+    }
+
+    // `--set general.version=3.12`
+    pub fn with_overrides<S: AsRef<str>>(mut self, overrides: &[S]) -> Result<Self, ProjectError> {
+        for entry in overrides {
+            let entry = entry.as_ref();
+            let (key, value) = entry
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value))
+                .filter(|(key, _)| !key.is_empty() && !key.split('.').any(str::is_empty))
+                .ok_or_else(|| {
+                    ProjectError::Config(format!(
+                        "Invalid --set '{}': expected section.key=value",
+                        entry
+                    ))
+                })?;
+            let path: Vec<String> = key.split('.').map(String::from).collect();
+            self.push_value(format!("cli (--set {})", key), &path, value.to_string());
+        }
+        Ok(self)
+    }
+
+    // A dedicated flag such as `--env uv` setting one dotted key
+    pub fn with_flag(mut self, flag: &str, key: &str, value: &str) -> Self {
+        let path: Vec<String> = key.split('.').map(String::from).collect();
+        self.push_value(format!("cli ({})", flag), &path, value.to_string());
+        self
+    }
+
+    fn push_value(&mut self, origin: String, key: &[String], value: String) {
+        let mut value = toml::Value::String(value);
+        for part in key.iter().rev() {
+            let mut table = toml::Table::new();
+            table.insert(part.clone(), value);
+            value = toml::Value::Table(table);
+        }
+        if let toml::Value::Table(table) = value {
+            self.layers.push(Layer {
+                source: Source { origin, file: None },
+                table,
+                untyped: true,
+            });
+        }
+    }
+
+    // `general.language` in a layer picks the language when none is given;
+    // the last layer setting it wins.
+    pub fn language(&self) -> Option<String> {
+        self.layers.iter().rev().find_map(|layer| {
+            layer
+                .table
+                .get("general")
+                .and_then(|general| general.get("language"))
+                .and_then(toml::Value::as_str)
+                .map(String::from)
+        })
+    }
+
+    pub fn resolve(&self, language: &str) -> Result<ResolvedConfig, ProjectError> {
+        let builtin = Config::builtin(language)?;
+        let mut table = match toml::Value::try_from(&builtin) {
+            Ok(toml::Value::Table(table)) => table,
+            _ => {
+                return Err(ProjectError::Config(format!(
+                    "Failed to read built-in config for '{}'",
+                    language
+                )))
+            }
+        };
+        let builtin_path = format!("config/{}/{}", language, DEFAULT_CONFIG_FILE);
+        let mut sources = vec![Source {
+            origin: format!("built-in ({})", builtin_path),
+            file: builtin_source(language).map(|text| (builtin_path, text.to_string())),
+        }];
+        let mut origins = BTreeMap::new();
+        record_origins(&table, "", 0, &mut origins);
+        let defaults = table.clone();
+
+        let languages = supported_languages();
+        for layer in &self.layers {
+            sources.push(layer.source.clone());
+            let source = sources.len() - 1;
+            let mut generic = layer.table.clone();
+            generic.retain(|key, _| !languages.contains(&key));
+            let specific = match layer.table.get(language) {
+                Some(toml::Value::Table(specific)) => Some(specific.clone()),
+                _ => None,
+            };
+            for mut overlay in std::iter::once(generic).chain(specific) {
+                // The language is chosen before the layers are applied
+                if let Some(toml::Value::Table(general)) = overlay.get_mut("general") {
+                    general.remove("language");
+                }
+                merge(&mut table, overlay, "", layer, source, &mut origins);
+            }
+        }
+
+        let mut checked = table.clone();
+        let key_issues = check_keys(&mut checked, &defaults);
+        let config: Config = toml::Value::Table(checked)
+            .try_into()
+            .map_err(|e| ProjectError::Config(format!("Invalid configuration: {}", e)))?;
+        Ok(ResolvedConfig {
+            config,
+            language: language.to_string(),
+            sources,
+            origins,
+            table,
+            cli_templates_dirs: 0,
+            key_issues,
+        })
+    }
+}
+
+impl ResolvedConfig {
+    // Every leaf key as `section.key`, with its value and the layer it came from
+    pub fn entries(&self) -> Vec<(String, &toml::Value, &str)> {
+        let mut entries = Vec::new();
+        collect_leaves(&self.table, "", &mut entries);
+        entries
+            .into_iter()
+            .map(|(key, value)| {
+                let origin = self.origins.get(&key).copied().unwrap_or_default();
+                (key, value, self.sources[origin].origin.as_str())
+            })
+            .collect()
+    }
+
+    // `--templates-dir` is searched before the configured directories
+    pub fn prepend_templates_dirs(&mut self, dirs: Vec<PathBuf>) {
+        self.cli_templates_dirs += dirs.len();
+        self.config.templates.dirs.splice(0..0, dirs);
+    }
+
+    // Unknown and mistyped keys, then `Config::validate`, with each problem
+    // located in the layer that set the offending key
+    pub fn validate(&self) -> Result<(), ProjectError> {
+        let issues: Vec<ConfigIssue> = self
+            .key_issues
+            .iter()
+            .cloned()
+            .chain(self.config.issues())
+            .map(|issue| self.locate_issue(issue))
+            .collect();
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ProjectError::InvalidConfig(issues))
+        }
+    }
+
+    fn locate_issue(&self, issue: ConfigIssue) -> ConfigIssue {
+        let dir = issue
+            .key
+            .strip_prefix("templates.dirs[")
+            .and_then(|index| index.strip_suffix(']'))
+            .and_then(|index| index.parse::<usize>().ok());
+        let (key, location) = match dir {
+            Some(index) if index < self.cli_templates_dirs => {
+                (issue.key, "cli (--templates-dir)".to_string())
+            }
+            Some(index) => {
+                let key = format!("templates.dirs[{}]", index - self.cli_templates_dirs);
+                let location = self.locate(&key);
+                (key, location)
+            }
+            None => {
+                let location = self.locate(&issue.key);
+                (issue.key, location)
+            }
+        };
+        ConfigIssue {
+            key,
+            location: Some(location),
+            ..issue
+        }
+    }
+
+    // `path:line:column` for keys set in a file, otherwise the layer's name.
+    // `key[2]` points at an array element.
+    fn locate(&self, key: &str) -> String {
+        let (key, index) = match key.strip_suffix(']').and_then(|k| k.rsplit_once('[')) {
+            Some((key, index)) => (key, index.parse::<usize>().ok()),
+            None => (key, None),
+        };
+        // A whole table is located through the keys in it
+        let origin = self.origins.get(key).copied().or_else(|| {
+            let prefix = format!("{}.", key);
+            self.origins
+                .iter()
+                .find(|(k, _)| k.starts_with(&prefix))
+                .map(|(_, origin)| *origin)
+        });
+        let source = &self.sources[origin.unwrap_or_default()];
+        let Some((path, text)) = &source.file else {
+            return source.origin.clone();
+        };
+        let Ok(document) = toml_edit::ImDocument::parse(text.as_str()) else {
+            return source.origin.clone();
+        };
+        let parts: Vec<&str> = key.split('.').collect();
+        let specific: Vec<&str> = std::iter::once(self.language.as_str())
+            .chain(parts.iter().copied())
+            .collect();
+        let span = [specific, parts].iter().find_map(|parts| {
+            let item = parts
+                .iter()
+                .try_fold(document.as_item(), |item, part| item.get(part))?;
+            match index {
+                Some(index) => item.as_array()?.get(index)?.span(),
+                None => item.span(),
+            }
+        });
+        match span {
+            Some(span) => {
+                let before = &text[..span.start];
+                let line = before.matches('\n').count() + 1;
+                let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+                format!("{}:{}:{}", path, line, column)
+            }
+            None => path.clone(),
+        }
+    }
+}
+
+pub fn find_project_config(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+// Tables merge key by key; anything else, arrays included, replaces the
+// existing value.
+fn merge(
+    target: &mut toml::Table,
+    overlay: toml::Table,
+    prefix: &str,
+    layer: &Layer,
+    source: usize,
+    origins: &mut BTreeMap<String, usize>,
+) {
+    for (key, value) in overlay {
+        let path = join_key(prefix, &key);
+        match (target.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(value)) => {
+                merge(existing, value, &path, layer, source, origins)
+            }
+            // A new table, e.g. a project type defined with `--set`; merged
+            // into an empty one so that its strings are coerced too
+            (None, toml::Value::Table(value)) => {
+                let mut table = toml::Table::new();
+                merge(&mut table, value, &path, layer, source, origins);
+                target.insert(key, toml::Value::Table(table));
+            }
+            (existing, value) => {
+                let value = match (existing.as_deref(), value) {
+                    (existing, toml::Value::String(raw)) if layer.untyped => coerce(existing, raw),
+                    (_, value) => value,
+                };
+                origins.retain(|k, _| k != &path && !k.starts_with(&format!("{}.", path)));
+                record_origins_value(&value, &path, source, origins);
+                target.insert(key, value);
+            }
+        }
+    }
+}
+
+// A string stays a string unless it replaces a value of another type or
+// looks like an array.
+fn coerce(existing: Option<&toml::Value>, raw: String) -> toml::Value {
+    let parse = |raw: &str| {
+        format!("value = {}", raw)
+            .parse::<toml::Table>()
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+    };
+    match existing {
+        Some(toml::Value::String(_)) => toml::Value::String(raw),
+        None if !raw.trim_start().starts_with('[') => toml::Value::String(raw),
+        _ => parse(&raw).unwrap_or(toml::Value::String(raw)),
+    }
+}
+
+fn record_origins(
+    table: &toml::Table,
+    prefix: &str,
+    origin: usize,
+    origins: &mut BTreeMap<String, usize>,
+) {
+    for (key, value) in table {
+        record_origins_value(value, &join_key(prefix, key), origin, origins);
+    }
+}
+
+fn record_origins_value(
+    value: &toml::Value,
+    path: &str,
+    origin: usize,
+    origins: &mut BTreeMap<String, usize>,
+) {
+    match value {
+        toml::Value::Table(table) => record_origins(table, path, origin, origins),
+        _ => {
+            origins.insert(path.to_string(), origin);
+        }
+    }
+}
+
+fn collect_leaves<'a>(
+    table: &'a toml::Table,
+    prefix: &str,
+    entries: &mut Vec<(String, &'a toml::Value)>,
+) {
+    for (key, value) in table {
+        let path = join_key(prefix, key);
+        match value {
+            toml::Value::Table(table) => collect_leaves(table, &path, entries),
+            _ => entries.push((path, value)),
+        }
+    }
+}
@@ -1,41 +1,101 @@
+mod layers;
+mod validate;
+
+pub use layers::{find_project_config, ConfigLayers, ResolvedConfig, PROJECT_CONFIG_FILE};
+pub use validate::ConfigIssue;
+
 use crate::error::ProjectError;
+use include_dir::{include_dir, Dir};
 use log::{debug, error};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Serialize, Deserialize)]
+static BUILTIN_CONFIGS: Dir = include_dir!("$CARGO_MANIFEST_DIR/config");
+const DEFAULT_CONFIG_FILE: &str = "default.toml";
+
+// `main_file_template` is a path under the templates root; left out, the
+// project type's own main file is used.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ProjectTypeConfig {
+    // Runtime dependencies
+    #[serde(default)]
     pub dependencies: Vec<String>,
-    pub main_file_template: String,
+    // Development-only dependencies by group (dev, test, docs, ...); Python
+    // adds the test framework to `test`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, Vec<String>>,
+    // Optional runtime features, e.g. `[project.optional-dependencies]`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extras: BTreeMap<String, Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub main_file_template: Option<String>,
+    // Selects a template variant, e.g. `python/web/<framework>`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub framework: Option<String>,
+    // Console script target of packaged Python projects, e.g. `main:main`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_point: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     pub general: GeneralConfig,
-    pub web: ProjectTypeConfig,
-    pub cli: ProjectTypeConfig,
-    pub data_science: ProjectTypeConfig,
-    pub base: ProjectTypeConfig,
+    #[serde(default)]
+    pub project_types: BTreeMap<String, ProjectTypeConfig>,
     pub test: TestConfig,
+    // Language-wide options, e.g. Node's `package_manager`
+    #[serde(default)]
+    pub settings: BTreeMap<String, toml::Value>,
+    #[serde(default)]
+    pub templates: TemplatesConfig,
+    // Defaults for template variables, e.g. an org-wide `author`
+    #[serde(default)]
+    pub variables: BTreeMap<String, toml::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct GeneralConfig {
     pub language: String,
     pub version: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct TestConfig {
     pub framework: String,
 }
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplatesConfig {
+    #[serde(default)]
+    pub dirs: Vec<PathBuf>,
+}
+
 impl Config {
-    #[allow(dead_code)]
     pub fn new() -> Result<Self, ProjectError> {
-        toml::from_str(include_str!("../../config/python/default.toml"))
-            .map_err(|e| ProjectError::Config(format!("Failed to parse default config: {}", e)))
+        Self::builtin("python")
+    }
+
+    // The embedded `config/<language>/default.toml`
+    pub fn builtin(language: &str) -> Result<Self, ProjectError> {
+        let source = builtin_source(language).ok_or_else(|| {
+            ProjectError::Config(format!(
+                "No built-in configuration for language '{}'",
+                language
+            ))
+        })?;
+        toml::from_str(source).map_err(|e| {
+            ProjectError::Config(format!(
+                "Failed to parse default config for '{}': {}",
+                language, e
+            ))
+        })
     }
 
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ProjectError> {
@@ -68,20 +128,79 @@ impl Config {
         Ok(config)
     }
 
-    pub fn get_project_type_config(&self, project_type: &str) -> &ProjectTypeConfig {
-        match project_type {
-            "web" => &self.web,
-            "cli" => &self.cli,
-            "data_science" => &self.data_science,
-            _ => &self.base,
-        }
+    pub fn setting(&self, key: &str) -> Option<&toml::Value> {
+        self.settings.get(key)
     }
 
-    #[allow(dead_code)]
+    pub fn project_type_names(&self) -> Vec<String> {
+        self.project_types.keys().cloned().collect()
+    }
+
+    pub fn get_project_type_config(
+        &self,
+        project_type: &str,
+    ) -> Result<&ProjectTypeConfig, ProjectError> {
+        self.project_types
+            .get(project_type)
+            .ok_or_else(|| unknown_project_type(project_type, &self.project_type_names()))
+    }
+
+    // Every problem at once; see `ResolvedConfig::validate` for locations
     pub fn validate(&self) -> Result<(), ProjectError> {
-        if self.general.language.is_empty() {
-            return Err(ProjectError::Config("Language not specified".to_string()));
+        let issues = self.issues();
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ProjectError::InvalidConfig(issues))
         }
-        Ok(())
     }
 }
+
+fn builtin_source(language: &str) -> Option<&'static str> {
+    BUILTIN_CONFIGS
+        .get_file(Path::new(language).join(DEFAULT_CONFIG_FILE))
+        .and_then(|file| file.contents_utf8())
+}
+
+pub fn unknown_project_type<S: AsRef<str>>(project_type: &str, known: &[S]) -> ProjectError {
+    let known: Vec<&str> = known.iter().map(AsRef::as_ref).collect();
+    let suggestion = match did_you_mean(project_type, &known) {
+        Some(candidate) => format!(" (did you mean '{}'?)", candidate),
+        None => String::new(),
+    };
+    ProjectError::Config(format!(
+        "Unknown project type '{}'{}; available: {}",
+        project_type,
+        suggestion,
+        known.join(", ")
+    ))
+}
+
+// For `Language::check_setting`, which knows the settings of its language
+pub fn unknown_setting(key: &str, known: &[&str]) -> String {
+    match did_you_mean(key, known) {
+        Some(candidate) => format!("unknown setting '{}' (did you mean '{}'?)", key, candidate),
+        None if known.is_empty() => format!("unknown setting '{}'; there are no settings", key),
+        None => format!(
+            "unknown setting '{}' (expected one of: {})",
+            key,
+            known.join(", ")
+        ),
+    }
+}
+
+// The closest candidate, if it is close enough to be a likely typo; swapped
+// letters count as one edit
+pub fn did_you_mean<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|&candidate| {
+            (
+                candidate,
+                strsim::normalized_damerau_levenshtein(name, candidate),
+            )
+        })
+        .filter(|&(_, score)| score >= 0.6)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(candidate, _)| candidate)
+}
@@ -0,0 +1,318 @@
+use super::Config;
+use crate::languages::{get_language, supported_languages};
+use crate::templating::TemplateLoader;
+use regex::Regex;
+use std::fmt;
+use std::path::Path;
+use std::sync::LazyLock;
+
+// Group and extra names end up in file names (`requirements-<name>.txt`)
+static GROUP_NAME_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z0-9](?:[A-Za-z0-9_-]*[A-Za-z0-9])?$").unwrap());
+
+// `package.module:object.attribute`
+static ENTRY_POINT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z_][\w.]*:[A-Za-z_][\w.]*$").unwrap());
+
+// One problem found by `Config::validate`. `key` is dotted, with `[n]` for an
+// array element; `location` is `path:line:column` or the layer that set it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigIssue {
+    pub key: String,
+    pub message: String,
+    pub location: Option<String>,
+}
+
+impl ConfigIssue {
+    fn new(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            message: message.into(),
+            location: None,
+        }
+    }
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{}: {}: {}", location, self.key, self.message),
+            None => write!(f, "{}: {}", self.key, self.message),
+        }
+    }
+}
+
+impl Config {
+    pub fn issues(&self) -> Vec<ConfigIssue> {
+        let mut issues = Vec::new();
+        let language = get_language(&self.general.language);
+        match &language {
+            Some(language) => {
+                if let Err(e) = language.check_version(&self.general.version) {
+                    issues.push(ConfigIssue::new("general.version", e));
+                }
+            }
+            None => issues.push(ConfigIssue::new(
+                "general.language",
+                format!(
+                    "unsupported language '{}' (expected one of: {})",
+                    self.general.language,
+                    supported_languages().join(", ")
+                ),
+            )),
+        }
+
+        let loader = TemplateLoader::with_dirs(&self.templates.dirs);
+        for (name, project_type) in &self.project_types {
+            let key = format!("project_types.{}", name);
+            if let Some(language) = &language {
+                let lists =
+                    std::iter::once(("dependencies".to_string(), &project_type.dependencies))
+                        .chain(
+                            project_type
+                                .groups
+                                .iter()
+                                .map(|(name, deps)| (format!("groups.{}", name), deps)),
+                        )
+                        .chain(
+                            project_type
+                                .extras
+                                .iter()
+                                .map(|(name, deps)| (format!("extras.{}", name), deps)),
+                        );
+                for (list, specs) in lists {
+                    for (i, spec) in specs.iter().enumerate() {
+                        if let Err(e) = language.check_dependency(spec) {
+                            issues.push(ConfigIssue::new(format!("{}.{}[{}]", key, list, i), e));
+                        }
+                    }
+                }
+            }
+            if let Some(template) = &project_type.main_file_template {
+                if !loader.has_file(template) {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.main_file_template", key),
+                        format!("template '{}' not found", template),
+                    ));
+                }
+            }
+            for name in project_type.groups.keys() {
+                if !GROUP_NAME_RE.is_match(name) {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.groups.{}", key, name),
+                        format!("'{}' is not a valid group name", name),
+                    ));
+                } else if project_type.extras.contains_key(name) {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.extras.{}", key, name),
+                        format!("'{}' is already a dependency group", name),
+                    ));
+                }
+            }
+            for name in project_type.extras.keys() {
+                if !GROUP_NAME_RE.is_match(name) {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.extras.{}", key, name),
+                        format!("'{}' is not a valid extra name", name),
+                    ));
+                }
+            }
+            if let Some(entry_point) = &project_type.entry_point {
+                if !ENTRY_POINT_RE.is_match(entry_point) {
+                    issues.push(ConfigIssue::new(
+                        format!("{}.entry_point", key),
+                        format!("'{}' is not of the form module:function", entry_point),
+                    ));
+                }
+            }
+            let variant = match (&language, &project_type.framework) {
+                (Some(language), Some(framework))
+                    if language.framework_types().contains(&name.as_str()) =>
+                {
+                    Path::new(language.name()).join(name).join(framework)
+                }
+                _ => continue,
+            };
+            if !loader.has_dir(&variant) {
+                issues.push(ConfigIssue::new(
+                    format!("{}.framework", key),
+                    format!(
+                        "unsupported framework '{}': no '{}' templates",
+                        project_type.framework.as_deref().unwrap_or_default(),
+                        variant.display()
+                    ),
+                ));
+            }
+        }
+
+        if let Some(language) = &language {
+            let frameworks = language.test_frameworks();
+            if !frameworks.contains(&self.test.framework.as_str()) {
+                issues.push(ConfigIssue::new(
+                    "test.framework",
+                    format!(
+                        "unknown test framework '{}' (expected one of: {})",
+                        self.test.framework,
+                        frameworks.join(", ")
+                    ),
+                ));
+            }
+        }
+        if let Some(language) = &language {
+            for (key, value) in &self.settings {
+                if let Err(e) = language.check_setting(key, value) {
+                    issues.push(ConfigIssue::new(format!("settings.{}", key), e));
+                }
+            }
+        }
+        for (i, dir) in self.templates.dirs.iter().enumerate() {
+            if !dir.is_dir() {
+                issues.push(ConfigIssue::new(
+                    format!("templates.dirs[{}]", i),
+                    format!("directory '{}' does not exist", dir.display()),
+                ));
+            }
+        }
+        issues
+    }
+}
+
+// The layout of a config file, checked before it is read into a `Config` so
+// that every unknown or mistyped key is reported rather than the first one
+enum Shape {
+    String,
+    StringList,
+    // Known keys only
+    Table(&'static [(&'static str, Shape)]),
+    // Any key, each value of the given shape
+    Map(&'static Shape),
+    Any,
+}
+
+const DEPENDENCY_LISTS: Shape = Shape::Map(&Shape::StringList);
+
+const PROJECT_TYPE: Shape = Shape::Table(&[
+    ("dependencies", Shape::StringList),
+    ("groups", DEPENDENCY_LISTS),
+    ("extras", DEPENDENCY_LISTS),
+    ("main_file_template", Shape::String),
+    ("framework", Shape::String),
+    ("entry_point", Shape::String),
+]);
+
+const CONFIG: Shape = Shape::Table(&[
+    (
+        "general",
+        Shape::Table(&[("language", Shape::String), ("version", Shape::String)]),
+    ),
+    ("project_types", Shape::Map(&PROJECT_TYPE)),
+    ("test", Shape::Table(&[("framework", Shape::String)])),
+    ("settings", Shape::Map(&Shape::Any)),
+    ("templates", Shape::Table(&[("dirs", Shape::StringList)])),
+    ("variables", Shape::Map(&Shape::Any)),
+]);
+
+// Issues for keys of `table` that `Config` doesn't have or whose values have
+// the wrong type. Those are taken out of `table`, or reset to their value in
+// `defaults`, so that the rest of it can still be read and validated.
+pub(super) fn check_keys(table: &mut toml::Table, defaults: &toml::Table) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+    check_table(table, &CONFIG, "", Some(defaults), &mut issues);
+    issues
+}
+
+fn check_table(
+    table: &mut toml::Table,
+    shape: &Shape,
+    prefix: &str,
+    defaults: Option<&toml::Table>,
+    issues: &mut Vec<ConfigIssue>,
+) {
+    let keys: Vec<String> = table.keys().cloned().collect();
+    for key in keys {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        let value_shape = match shape {
+            Shape::Table(fields) => fields
+                .iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, shape)| shape),
+            Shape::Map(shape) => Some(*shape),
+            _ => None,
+        };
+        let Some(value_shape) = value_shape else {
+            let known: Vec<&str> = match shape {
+                Shape::Table(fields) => fields.iter().map(|(name, _)| *name).collect(),
+                _ => Vec::new(),
+            };
+            let message = match super::did_you_mean(&key, &known) {
+                Some(candidate) => format!("unknown key '{}' (did you mean '{}'?)", key, candidate),
+                None => format!(
+                    "unknown key '{}' (expected one of: {})",
+                    key,
+                    known.join(", ")
+                ),
+            };
+            issues.push(ConfigIssue::new(path, message));
+            table.remove(&key);
+            continue;
+        };
+        let default = defaults.and_then(|defaults| defaults.get(&key));
+        if !check_value(
+            table.get_mut(&key).unwrap(),
+            value_shape,
+            &path,
+            default,
+            issues,
+        ) {
+            match default {
+                Some(default) => table.insert(key, default.clone()),
+                None => table.remove(&key),
+            };
+        }
+    }
+}
+
+// False when the value itself has the wrong type and has to go
+fn check_value(
+    value: &mut toml::Value,
+    shape: &Shape,
+    path: &str,
+    default: Option<&toml::Value>,
+    issues: &mut Vec<ConfigIssue>,
+) -> bool {
+    let expected = match (shape, &mut *value) {
+        (Shape::Any, _) | (Shape::String, toml::Value::String(_)) => return true,
+        (Shape::StringList, toml::Value::Array(items)) => {
+            let mut index = 0;
+            items.retain(|item| {
+                let ok = item.is_str();
+                if !ok {
+                    issues.push(ConfigIssue::new(
+                        format!("{}[{}]", path, index),
+                        format!("expected a string, got {}", item),
+                    ));
+                }
+                index += 1;
+                ok
+            });
+            return true;
+        }
+        (Shape::Table(_) | Shape::Map(_), toml::Value::Table(table)) => {
+            let defaults = default.and_then(toml::Value::as_table);
+            check_table(table, shape, path, defaults, issues);
+            return true;
+        }
+        (Shape::String, _) => "a string",
+        (Shape::StringList, _) => "a list of strings",
+        (Shape::Table(_) | Shape::Map(_), _) => "a table",
+    };
+    issues.push(ConfigIssue::new(
+        path,
+        format!("expected {}, got {}", expected, value),
+    ));
+    false
+}
@@ -0,0 +1,129 @@
+use crate::core::plan::Plan;
+use crate::error::ProjectError;
+use log::{debug, info};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    #[default]
+    Abort,
+    Overwrite,
+    Skip,
+    Prompt,
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = ProjectError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "abort" => Ok(Self::Abort),
+            "overwrite" => Ok(Self::Overwrite),
+            "skip" => Ok(Self::Skip),
+            "prompt" => Ok(Self::Prompt),
+            other => Err(ProjectError::Config(format!(
+                "Unknown conflict policy '{}': expected abort, overwrite, skip or prompt",
+                other
+            ))),
+        }
+    }
+}
+
+pub struct Conflict<'a> {
+    pub path: &'a Path,
+    pub existing: Vec<u8>,
+    pub generated: &'a [u8],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Overwrite,
+    Skip,
+    OverwriteAll,
+    SkipAll,
+    Abort,
+}
+
+#[derive(Debug, Default)]
+pub struct ConflictReport {
+    pub overwritten: Vec<PathBuf>,
+    pub skipped: Vec<PathBuf>,
+    pub unchanged: Vec<PathBuf>,
+}
+
+// Compares every file in `plan` with what is already under `root` and drops
+// or keeps the write according to `policy`. Files whose content would not
+// change are dropped silently. `prompt` is only called for `Prompt`.
+pub fn resolve_conflicts<F>(
+    plan: &mut Plan,
+    root: &Path,
+    policy: ConflictPolicy,
+    mut prompt: F,
+) -> Result<ConflictReport, ProjectError>
+where
+    F: FnMut(&Conflict) -> Result<Resolution, ProjectError>,
+{
+    let mut report = ConflictReport::default();
+    let mut conflicts = Vec::new();
+    let mut policy = policy;
+
+    let planned: Vec<(PathBuf, Vec<u8>)> = plan
+        .files()
+        .map(|(path, contents)| (path.to_path_buf(), contents.to_vec()))
+        .collect();
+    for (path, generated) in planned {
+        let target = root.join(&path);
+        if !target.exists() {
+            continue;
+        }
+        let existing = fs::read(&target).map_err(|e| {
+            ProjectError::Generator(format!("Cannot replace '{}': {}", target.display(), e))
+        })?;
+        if existing == generated {
+            debug!("'{}' is unchanged", path.display());
+            plan.remove_file(&path);
+            report.unchanged.push(path);
+            continue;
+        }
+
+        let resolution = match policy {
+            ConflictPolicy::Abort => {
+                conflicts.push(path);
+                continue;
+            }
+            ConflictPolicy::Overwrite => Resolution::Overwrite,
+            ConflictPolicy::Skip => Resolution::Skip,
+            ConflictPolicy::Prompt => prompt(&Conflict {
+                path: &path,
+                existing,
+                generated: &generated,
+            })?,
+        };
+        match resolution {
+            Resolution::Overwrite | Resolution::OverwriteAll => {
+                if resolution == Resolution::OverwriteAll {
+                    policy = ConflictPolicy::Overwrite;
+                }
+                info!("Overwriting '{}'", path.display());
+                report.overwritten.push(path);
+            }
+            Resolution::Skip | Resolution::SkipAll => {
+                if resolution == Resolution::SkipAll {
+                    policy = ConflictPolicy::Skip;
+                }
+                info!("Keeping existing '{}'", path.display());
+                plan.remove_file(&path);
+                report.skipped.push(path);
+            }
+            Resolution::Abort => return Err(ProjectError::Conflict(vec![path])),
+        }
+    }
+
+    if conflicts.is_empty() {
+        Ok(report)
+    } else {
+        Err(ProjectError::Conflict(conflicts))
+    }
+}
@@ -8,7 +8,6 @@ pub struct RuntimeContext {
     pub project_type: String,
     #[allow(dead_code)]
     pub project_name: String,
-    #[allow(dead_code)]
     pub project_path: PathBuf,
 }
 
@@ -28,7 +27,6 @@ impl RuntimeContext {
         }
     }
 
-    #[allow(dead_code)]
     pub fn with_path(mut self, path: PathBuf) -> Self {
         self.project_path = path;
         self
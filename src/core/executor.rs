@@ -0,0 +1,242 @@
+use crate::core::plan::{CommandSpec, Operation, Plan};
+use crate::core::transaction::Transaction;
+use crate::error::ProjectError;
+use crate::utils::{create_directory, write_file};
+use log::{debug, error, info, warn};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Lines of output kept for the error of a failed command
+const OUTPUT_TAIL_LINES: usize = 20;
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Default)]
+pub struct Executor {
+    keep_on_failure: bool,
+    verbose: bool,
+    timeout: Option<Duration>,
+}
+
+impl Executor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn keep_on_failure(mut self, keep: bool) -> Self {
+        self.keep_on_failure = keep;
+        self
+    }
+
+    // Streams the output of commands as they run instead of only keeping
+    // its tail for errors
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    // Commands still running after `timeout` are killed
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    // Files are staged and moved into `root` only once all of them have been
+    // written; commands then run in `root`. Any failure rolls back what was
+    // done unless `keep_on_failure` is set.
+    pub fn execute(&self, plan: &Plan, root: &Path) -> Result<(), ProjectError> {
+        debug!("Executing plan in: {:?}", root);
+        let mut transaction = Transaction::begin(root)?;
+        match self.run(plan, &mut transaction) {
+            Ok(()) => {
+                transaction.finish();
+                Ok(())
+            }
+            Err(e) if self.keep_on_failure => {
+                let location = if transaction.is_committed() {
+                    transaction.target()
+                } else {
+                    transaction.staging_dir()
+                };
+                warn!(
+                    "Keeping partially generated files in: {}",
+                    location.display()
+                );
+                Err(e)
+            }
+            Err(e) => {
+                transaction.rollback();
+                Err(e)
+            }
+        }
+    }
+
+    fn run(&self, plan: &Plan, transaction: &mut Transaction) -> Result<(), ProjectError> {
+        let staging = transaction.staging_dir().to_path_buf();
+        for op in plan.operations() {
+            match op {
+                Operation::CreateDirectory(path) => create_directory(staging.join(path))?,
+                Operation::WriteFile { path, contents } => {
+                    write_file(staging.join(path), contents)?
+                }
+                Operation::RunCommand(_) => {}
+            }
+        }
+        transaction.commit()?;
+
+        for command in plan.commands() {
+            self.run_command(command, transaction.target())?;
+        }
+        Ok(())
+    }
+
+    // Also runs one-off commands outside a plan, such as `poq wheelhouse fetch`
+    pub fn run_command(&self, command: &CommandSpec, root: &Path) -> Result<(), ProjectError> {
+        info!("Running: {}", command.command_line());
+        match self.spawn(command, root, false) {
+            Ok(_) => Ok(()),
+            Err(e) if command.optional => {
+                warn!(
+                    "Skipped optional step ({}): {}",
+                    command.description,
+                    e.detail()
+                );
+                Ok(())
+            }
+            Err(e) => {
+                error!("Failed to {}: {}", command.description, e.detail());
+                Err(e)
+            }
+        }
+    }
+
+    // Runs a command for its output, such as a report printed to stdout, with
+    // the same timeout and error handling; only stderr is streamed
+    pub fn capture(&self, command: &CommandSpec, dir: &Path) -> Result<String, ProjectError> {
+        info!("Running: {}", command.command_line());
+        self.spawn(command, dir, true).inspect_err(|e| {
+            error!("Failed to {}: {}", command.description, e.detail());
+        })
+    }
+
+    // Returns all of stdout when capturing, otherwise its last lines
+    fn spawn(
+        &self,
+        command: &CommandSpec,
+        root: &Path,
+        capture: bool,
+    ) -> Result<String, ProjectError> {
+        let mut child = Command::new(resolve_program(&command.program, root))
+            .args(&command.args)
+            .envs(command.env.iter().map(|(key, value)| (key, value)))
+            .current_dir(root)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                ProjectError::Generator(format!("Failed to {}: {}", command.description, e))
+            })?;
+        let verbose = self.verbose;
+        let stdout = child.stdout.take().map(|mut out| {
+            thread::spawn(move || {
+                if capture {
+                    let mut all = String::new();
+                    let _ = out.read_to_string(&mut all);
+                    all
+                } else {
+                    collect_output(out, verbose.then(io::stdout))
+                }
+            })
+        });
+        let stderr = child
+            .stderr
+            .take()
+            .map(|err| thread::spawn(move || collect_output(err, verbose.then(io::stderr))));
+
+        let Some(status) = self.wait(&mut child)? else {
+            // The readers are left behind: anything the command spawned may
+            // still hold its pipes open.
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(ProjectError::CommandTimedOut {
+                command: command.command_line(),
+                timeout: self.timeout.unwrap_or_default(),
+            });
+        };
+        let join = |reader: Option<thread::JoinHandle<String>>| {
+            reader
+                .and_then(|reader| reader.join().ok())
+                .unwrap_or_default()
+        };
+        let (stdout, stderr) = (join(stdout), join(stderr));
+        if status.success() {
+            return Ok(stdout);
+        }
+        // Some tools report errors on stdout
+        Err(ProjectError::CommandFailed {
+            command: command.command_line(),
+            code: status.code(),
+            stderr: if stderr.is_empty() {
+                last_lines(&stdout)
+            } else {
+                stderr
+            },
+        })
+    }
+
+    // `None` once the timeout has passed
+    fn wait(&self, child: &mut Child) -> io::Result<Option<ExitStatus>> {
+        let Some(timeout) = self.timeout else {
+            return child.wait().map(Some);
+        };
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return Ok(Some(status));
+            }
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+// Reads a command's output to the end, echoing it when streaming, and
+// returns its last lines.
+fn collect_output(output: impl Read, mut echo: Option<impl Write>) -> String {
+    let mut tail = VecDeque::with_capacity(OUTPUT_TAIL_LINES);
+    let mut reader = BufReader::new(output);
+    let mut line = Vec::new();
+    while matches!(reader.read_until(b'\n', &mut line), Ok(n) if n > 0) {
+        if let Some(echo) = echo.as_mut() {
+            let _ = echo.write_all(&line);
+        }
+        if tail.len() == OUTPUT_TAIL_LINES {
+            tail.pop_front();
+        }
+        tail.push_back(String::from_utf8_lossy(&line).trim_end().to_string());
+        line.clear();
+    }
+    Vec::from(tail).join("\n").trim_matches('\n').to_string()
+}
+
+fn last_lines(output: &str) -> String {
+    let lines: Vec<&str> = output.trim_matches('\n').lines().collect();
+    lines[lines.len().saturating_sub(OUTPUT_TAIL_LINES)..].join("\n")
+}
+
+// Programs given as a relative path (e.g. `venv/bin/pip`) live inside the
+// project; bare names are looked up on PATH.
+fn resolve_program(program: &str, root: &Path) -> PathBuf {
+    let path = Path::new(program);
+    if path.is_relative() && path.components().count() > 1 {
+        root.join(path)
+    } else {
+        path.to_path_buf()
+    }
+}
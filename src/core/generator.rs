@@ -1,7 +1,12 @@
 use crate::config::Config;
+use crate::core::plan::Plan;
 use crate::core::project::Project;
 use crate::error::ProjectError;
+use crate::templating::TemplateManifest;
+use serde_json::Value;
 
 pub trait ProjectGenerator {
-    fn generate(&self, project: &Project, config: &Config) -> Result<(), ProjectError>;
+    fn manifest(&self) -> &TemplateManifest;
+    fn create_context(&self, project: &Project, config: &Config) -> Value;
+    fn plan(&self, project: &Project, config: &Config) -> Result<Plan, ProjectError>;
 }
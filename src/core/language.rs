@@ -1,9 +1,63 @@
-use crate::config::Config;
+use crate::config::{unknown_setting, Config};
+use crate::core::executor::Executor;
 use crate::core::generator::ProjectGenerator;
+use crate::core::plan::Plan;
 use crate::core::project::Project;
 use crate::error::ProjectError;
 
 pub trait Language {
-    fn get_generator(&self, config: &Config, project_type: &str) -> Box<dyn ProjectGenerator>;
-    fn setup_environment(&self, project: &Project, config: &Config) -> Result<(), ProjectError>;
+    // Matches `general.language` in the language's config
+    fn name(&self) -> &'static str;
+
+    fn project_types(&self, config: &Config) -> Vec<String> {
+        config.project_type_names()
+    }
+
+    // Checks used by `Config::validate`; errors are messages for the key
+    fn check_version(&self, version: &str) -> Result<(), String>;
+    fn check_dependency(&self, spec: &str) -> Result<(), String>;
+    fn test_frameworks(&self) -> &'static [&'static str];
+
+    // Project types whose `framework` picks a `<language>/<type>/<framework>`
+    // template variant
+    fn framework_types(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    // Checks one `[settings]` entry; languages without settings reject
+    // every key
+    fn check_setting(&self, key: &str, _value: &toml::Value) -> Result<(), String> {
+        Err(unknown_setting(key, &[]))
+    }
+
+    // Finds the tools generation needs (e.g. a matching interpreter) and
+    // records them in `[settings]`
+    fn resolve_toolchain(&self, _config: &mut Config) -> Result<(), ProjectError> {
+        Ok(())
+    }
+
+    // Fixes the versions of `project_type`'s dependencies when asked to (e.g.
+    // `--pin`) and records them in `[settings]`; tools run through `executor`
+    fn resolve_dependencies(
+        &self,
+        _config: &mut Config,
+        _project_type: &str,
+        _executor: &Executor,
+    ) -> Result<(), ProjectError> {
+        Ok(())
+    }
+
+    // Said in a dry run's preview about what the two hooks above would have
+    // done
+    fn dry_run_note(&self, _config: &Config) -> Option<String> {
+        None
+    }
+
+    fn get_generator(
+        &self,
+        config: &Config,
+        project_type: &str,
+    ) -> Result<Box<dyn ProjectGenerator>, ProjectError>;
+    fn setup_environment(&self, project: &Project, config: &Config) -> Result<Plan, ProjectError>;
+    fn next_steps(&self, project: &Project, config: &Config) -> Vec<String>;
 }
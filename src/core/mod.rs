@@ -1,6 +1,11 @@
+pub mod conflicts;
 pub mod context;
+pub mod executor;
 pub mod generator;
 pub mod language;
+pub mod plan;
 pub mod project;
+pub mod transaction;
 
 pub use context::RuntimeContext;
+pub use plan::{CommandSpec, Operation, Plan};
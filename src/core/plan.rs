@@ -0,0 +1,185 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandSpec {
+    pub program: String,
+    pub args: Vec<String>,
+    pub description: String,
+    pub optional: bool,
+    // Extra environment variables for the command
+    pub env: Vec<(String, String)>,
+}
+
+impl CommandSpec {
+    pub fn new<S: Into<String>>(program: S, args: &[&str], description: &str) -> Self {
+        Self {
+            program: program.into(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            description: description.to_string(),
+            optional: false,
+            env: Vec::new(),
+        }
+    }
+
+    pub fn with_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn with_env(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.env.push((key.to_string(), value.into()));
+        self
+    }
+
+    // Optional commands (e.g. installs that need the network) only warn when
+    // they fail instead of failing the generation.
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    pub fn command_line(&self) -> String {
+        let env = self
+            .env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value));
+        env.chain(std::iter::once(self.program.clone()))
+            .chain(self.args.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+// Paths are relative to the project root; commands run inside it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    CreateDirectory(PathBuf),
+    WriteFile { path: PathBuf, contents: Vec<u8> },
+    RunCommand(CommandSpec),
+}
+
+#[derive(Debug, Default)]
+pub struct Plan {
+    operations: Vec<Operation>,
+}
+
+impl Plan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    pub fn create_directory<P: Into<PathBuf>>(&mut self, path: P) {
+        let path = path.into();
+        let planned = self
+            .operations
+            .iter()
+            .any(|op| matches!(op, Operation::CreateDirectory(p) if *p == path));
+        if !planned && !path.as_os_str().is_empty() {
+            self.operations.push(Operation::CreateDirectory(path));
+        }
+    }
+
+    // Parent directories are planned automatically. A later write to the same
+    // path replaces the earlier one.
+    pub fn write_file<P: Into<PathBuf>, C: Into<Vec<u8>>>(&mut self, path: P, contents: C) {
+        let path = path.into();
+        let parents: Vec<PathBuf> = path.ancestors().skip(1).map(Path::to_path_buf).collect();
+        for parent in parents.into_iter().rev() {
+            self.create_directory(parent);
+        }
+        self.operations
+            .retain(|op| !matches!(op, Operation::WriteFile { path: p, .. } if *p == path));
+        self.operations.push(Operation::WriteFile {
+            path,
+            contents: contents.into(),
+        });
+    }
+
+    pub fn remove_file(&mut self, path: &Path) {
+        self.operations
+            .retain(|op| !matches!(op, Operation::WriteFile { path: p, .. } if p == path));
+    }
+
+    pub fn run_command(&mut self, command: CommandSpec) {
+        self.operations.push(Operation::RunCommand(command));
+    }
+
+    pub fn extend(&mut self, other: Plan) {
+        for op in other.operations {
+            match op {
+                Operation::CreateDirectory(path) => self.create_directory(path),
+                Operation::WriteFile { path, contents } => self.write_file(path, contents),
+                Operation::RunCommand(command) => self.run_command(command),
+            }
+        }
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = (&Path, &[u8])> {
+        self.operations.iter().filter_map(|op| match op {
+            Operation::WriteFile { path, contents } => Some((path.as_path(), contents.as_slice())),
+            _ => None,
+        })
+    }
+
+    pub fn commands(&self) -> impl Iterator<Item = &CommandSpec> {
+        self.operations.iter().filter_map(|op| match op {
+            Operation::RunCommand(command) => Some(command),
+            _ => None,
+        })
+    }
+
+    // Human-readable listing of what executing the plan under `root` would do
+    pub fn describe(&self, root: &Path) -> String {
+        let mut out = String::new();
+        if !root.exists() {
+            let _ = writeln!(out, "  create dir   {}", root.display());
+        }
+        for op in &self.operations {
+            let _ = match op {
+                Operation::CreateDirectory(path) => {
+                    let target = root.join(path);
+                    if target.is_dir() {
+                        continue;
+                    }
+                    writeln!(out, "  create dir   {}", target.display())
+                }
+                Operation::WriteFile { path, contents } => {
+                    let target = root.join(path);
+                    match fs::metadata(&target) {
+                        Ok(existing) => writeln!(
+                            out,
+                            "  overwrite    {} ({} bytes, currently {} bytes)",
+                            target.display(),
+                            contents.len(),
+                            existing.len()
+                        ),
+                        Err(_) => writeln!(
+                            out,
+                            "  create file  {} ({} bytes)",
+                            target.display(),
+                            contents.len()
+                        ),
+                    }
+                }
+                Operation::RunCommand(command) => writeln!(
+                    out,
+                    "  run          {} ({}{})",
+                    command.command_line(),
+                    command.description,
+                    if command.optional { ", optional" } else { "" }
+                ),
+            };
+        }
+        out
+    }
+}
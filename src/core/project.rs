@@ -1,8 +1,10 @@
+use serde_json::{Map, Value};
 use std::path::PathBuf;
 
 pub struct Project {
     pub name: String,
     pub path: PathBuf,
+    pub variables: Map<String, Value>,
 }
 
 impl Project {
@@ -10,6 +12,12 @@ impl Project {
         Self {
             name: name.to_string(),
             path,
+            variables: Map::new(),
         }
     }
+
+    pub fn with_variables(mut self, variables: Map<String, Value>) -> Self {
+        self.variables = variables;
+        self
+    }
 }
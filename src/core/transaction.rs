@@ -0,0 +1,238 @@
+use crate::error::ProjectError;
+use log::{debug, error, warn};
+use std::collections::BTreeSet;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Files are written into a staging directory next to the target and moved
+// into place on commit. If the target did not exist the staging directory is
+// simply renamed; otherwise files are moved one by one, backing up anything
+// they replace so a rollback can restore it. A rollback also removes anything
+// new at the top of an existing target, such as a `venv/` left by a failed
+// setup command.
+pub struct Transaction {
+    target: PathBuf,
+    staging: PathBuf,
+    backup: PathBuf,
+    target_existed: bool,
+    original_entries: BTreeSet<OsString>,
+    committed: bool,
+    moved_files: Vec<PathBuf>,
+    created_dirs: Vec<PathBuf>,
+    backed_up: Vec<PathBuf>,
+}
+
+impl Transaction {
+    pub fn begin(target: &Path) -> Result<Self, ProjectError> {
+        let target = absolute(target)?;
+        let name = target
+            .file_name()
+            .ok_or_else(|| {
+                ProjectError::Generator(format!(
+                    "Cannot generate into '{}': it has no parent directory",
+                    target.display()
+                ))
+            })?
+            .to_string_lossy()
+            .into_owned();
+        let parent = target.parent().unwrap_or(Path::new("/"));
+        let staging = parent.join(format!(".{}.poq-staging-{}", name, std::process::id()));
+        let backup = parent.join(format!(".{}.poq-backup-{}", name, std::process::id()));
+
+        debug!("Staging generated files in: {:?}", staging);
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        fs::create_dir_all(&staging).map_err(|e| {
+            error!(
+                "Failed to create staging directory '{}': {}",
+                staging.display(),
+                e
+            );
+            ProjectError::Io(e)
+        })?;
+
+        let target_existed = target.exists();
+        let original_entries = if target.is_dir() {
+            top_level_entries(&target)?
+        } else {
+            BTreeSet::new()
+        };
+        Ok(Self {
+            target_existed,
+            original_entries,
+            target,
+            staging,
+            backup,
+            committed: false,
+            moved_files: Vec::new(),
+            created_dirs: Vec::new(),
+            backed_up: Vec::new(),
+        })
+    }
+
+    pub fn staging_dir(&self) -> &Path {
+        &self.staging
+    }
+
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    pub fn is_committed(&self) -> bool {
+        self.committed
+    }
+
+    pub fn commit(&mut self) -> Result<(), ProjectError> {
+        debug!("Moving staged files into: {:?}", self.target);
+        if self.target_existed {
+            let mut entries = Vec::new();
+            walk(&self.staging, Path::new(""), &mut entries)?;
+            for (relative, is_dir) in entries {
+                self.move_into_target(&relative, is_dir)?;
+            }
+            fs::remove_dir_all(&self.staging)?;
+        } else {
+            fs::rename(&self.staging, &self.target).map_err(|e| {
+                error!(
+                    "Failed to move '{}' to '{}': {}",
+                    self.staging.display(),
+                    self.target.display(),
+                    e
+                );
+                ProjectError::Io(e)
+            })?;
+        }
+        self.committed = true;
+        Ok(())
+    }
+
+    fn move_into_target(&mut self, relative: &Path, is_dir: bool) -> Result<(), ProjectError> {
+        let dest = self.target.join(relative);
+        if is_dir {
+            if !dest.is_dir() {
+                fs::create_dir(&dest)?;
+                self.created_dirs.push(dest);
+            }
+            return Ok(());
+        }
+        if dest.exists() {
+            let backup = self.backup.join(relative);
+            if let Some(parent) = backup.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(&dest, &backup)?;
+            self.backed_up.push(relative.to_path_buf());
+        }
+        fs::rename(self.staging.join(relative), &dest)?;
+        self.moved_files.push(dest);
+        Ok(())
+    }
+
+    pub fn finish(self) {
+        if self.backup.exists() {
+            if let Err(e) = fs::remove_dir_all(&self.backup) {
+                warn!("Failed to remove '{}': {}", self.backup.display(), e);
+            }
+        }
+    }
+
+    // Undoes everything this transaction did to the target; errors are logged
+    // rather than returned so they don't mask the original failure.
+    pub fn rollback(self) {
+        warn!("Rolling back changes to: {:?}", self.target);
+        let result = if self.target_existed {
+            self.restore_target()
+        } else if self.committed {
+            remove_if_exists(&self.target)
+        } else {
+            remove_if_exists(&self.staging)
+        };
+        if let Err(e) = result {
+            error!("Rollback of '{}' failed: {}", self.target.display(), e);
+        }
+        if let Err(e) = remove_if_exists(&self.backup) {
+            error!("Failed to remove '{}': {}", self.backup.display(), e);
+        }
+    }
+
+    // Keeps going after a failed step so that as much as possible is undone;
+    // the first error is returned
+    fn restore_target(&self) -> io::Result<()> {
+        let mut result = remove_if_exists(&self.staging);
+        let mut step = |outcome: io::Result<()>| {
+            if let Err(e) = outcome {
+                warn!("Rollback step failed: {}", e);
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        };
+        for file in &self.moved_files {
+            step(remove_if_exists(file));
+        }
+        for relative in &self.backed_up {
+            step(fs::rename(
+                self.backup.join(relative),
+                self.target.join(relative),
+            ));
+        }
+        match top_level_entries(&self.target) {
+            Ok(entries) => {
+                for name in entries.difference(&self.original_entries) {
+                    step(remove_if_exists(&self.target.join(name)));
+                }
+            }
+            Err(e) => step(Err(e)),
+        }
+        for dir in self.created_dirs.iter().rev() {
+            step(remove_if_exists(dir));
+        }
+        result
+    }
+}
+
+fn absolute(path: &Path) -> Result<PathBuf, ProjectError> {
+    if path.exists() {
+        return Ok(fs::canonicalize(path)?);
+    }
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
+    } else {
+        Ok(env::current_dir()?.join(path))
+    }
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+fn top_level_entries(dir: &Path) -> io::Result<BTreeSet<OsString>> {
+    fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.file_name()))
+        .collect()
+}
+
+// Directories come before their contents
+fn walk(root: &Path, relative: &Path, entries: &mut Vec<(PathBuf, bool)>) -> io::Result<()> {
+    let mut children = fs::read_dir(root.join(relative))?.collect::<Result<Vec<_>, _>>()?;
+    children.sort_by_key(|entry| entry.file_name());
+    for child in children {
+        let path = relative.join(child.file_name());
+        if child.file_type()?.is_dir() {
+            entries.push((path.clone(), true));
+            walk(root, &path, entries)?;
+        } else {
+            entries.push((path, false));
+        }
+    }
+    Ok(())
+}
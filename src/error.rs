@@ -1,3 +1,6 @@
+use crate::config::ConfigIssue;
+use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -8,6 +11,75 @@ pub enum ProjectError {
     Template(#[from] handlebars::RenderError),
     #[error("Configuration error: {0}")]
     Config(String),
+    #[error("Invalid configuration:\n{}", display_issues(.0))]
+    InvalidConfig(Vec<ConfigIssue>),
     #[error("Generator error: {0}")]
     Generator(String),
+    #[error("Command `{command}` {}{}", display_exit(*.code), display_tail(.stderr))]
+    CommandFailed {
+        command: String,
+        code: Option<i32>,
+        stderr: String,
+    },
+    #[error("Command `{command}` timed out after {}s", .timeout.as_secs())]
+    CommandTimedOut { command: String, timeout: Duration },
+    #[error(
+        "No Python interpreter matches '{constraint}' (found: {})",
+        if .found.is_empty() { "none".to_string() } else { .found.join(", ") }
+    )]
+    NoInterpreter {
+        constraint: String,
+        found: Vec<String>,
+    },
+    #[error("Missing required input: {}", .0.join(", "))]
+    MissingInput(Vec<String>),
+    #[error(
+        "Refusing to overwrite existing files: {} (use --force, --skip-existing or --on-conflict prompt)",
+        display_paths(.0)
+    )]
+    Conflict(Vec<PathBuf>),
+    #[error("Prompt error: {0}")]
+    Prompt(#[from] dialoguer::Error),
+}
+
+fn display_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn display_exit(code: Option<i32>) -> String {
+    match code {
+        Some(code) => format!("exited with code {}", code),
+        None => "was terminated by a signal".to_string(),
+    }
+}
+
+fn display_tail(output: &str) -> String {
+    if output.is_empty() {
+        return String::new();
+    }
+    let lines: Vec<String> = output.lines().map(|line| format!("  {}", line)).collect();
+    format!(":\n{}", lines.join("\n"))
+}
+
+fn display_issues(issues: &[ConfigIssue]) -> String {
+    issues
+        .iter()
+        .map(|issue| format!("  {}", issue))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+impl ProjectError {
+    // The message without the "Configuration error: " style prefix, for
+    // errors reported as part of another one
+    pub fn detail(&self) -> String {
+        match self {
+            ProjectError::Config(message) | ProjectError::Generator(message) => message.clone(),
+            other => other.to_string(),
+        }
+    }
 }
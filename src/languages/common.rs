@@ -0,0 +1,84 @@
+use crate::config::Config;
+use crate::core::plan::Plan;
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use crate::templating::{TemplateManifest, TemplatingEngine};
+use crate::utils::{language_context, merge_contexts, project_context};
+use log::debug;
+use serde_json::Value;
+use std::path::PathBuf;
+
+const BASE_TEMPLATE_DIR: &str = "base";
+
+// Template handling shared by every language: `<language>/base` is loaded
+// first and the project type's tree is layered on top of it.
+pub struct GeneratorBase {
+    pub engine: TemplatingEngine,
+    language: &'static str,
+    template_dir: PathBuf,
+}
+
+impl GeneratorBase {
+    pub fn new(
+        language: &'static str,
+        project_type: &str,
+        config: &Config,
+    ) -> Result<Self, ProjectError> {
+        let template_dir = PathBuf::from(language).join(project_type);
+        let mut engine = TemplatingEngine::with_search_path(&config.templates.dirs);
+        engine.load_templates_from_dir(PathBuf::from(language).join(BASE_TEMPLATE_DIR))?;
+        if let Some(main) = config
+            .project_types
+            .get(project_type)
+            .and_then(|t| t.main_file_template.as_deref())
+        {
+            engine.set_main_template(main);
+        }
+
+        Ok(Self {
+            engine,
+            language,
+            template_dir,
+        })
+    }
+
+    // Layers the project type's template tree (or one of its variants, such as
+    // `web/fastapi`) over the base tree.
+    pub fn load_type_templates(&mut self, variant: Option<&str>) -> Result<(), ProjectError> {
+        let dir = match variant {
+            Some(variant) => self.template_dir.join(variant),
+            None => self.template_dir.clone(),
+        };
+        self.engine.load_templates_from_dir(dir)
+    }
+
+    pub fn has_type_templates(&self) -> bool {
+        self.engine.has_template_dir(&self.template_dir)
+    }
+
+    pub fn create_context(&self, project: &Project, config: &Config) -> Value {
+        let project_ctx = project_context(&project.name);
+        // The loaded config only describes the language it was written for
+        let version = if config.general.language == self.language {
+            config.general.version.as_str()
+        } else {
+            ""
+        };
+        let language_ctx = language_context(self.language, version);
+        let variables = Value::Object(project.variables.clone());
+        merge_contexts(&[&project_ctx, &language_ctx, &variables])
+    }
+
+    pub fn manifest(&self) -> &TemplateManifest {
+        self.engine.manifest()
+    }
+
+    // For languages that add their own entries to the template context
+    pub fn render_files(&self, plan: &mut Plan, context: &Value) -> Result<(), ProjectError> {
+        debug!("Rendering template tree");
+        for file in self.engine.render_tree(context)? {
+            plan.write_file(file.path, file.contents);
+        }
+        Ok(())
+    }
+}
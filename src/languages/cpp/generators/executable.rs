@@ -0,0 +1,43 @@
+use super::LANGUAGE;
+use crate::config::Config;
+use crate::core::generator::ProjectGenerator;
+use crate::core::plan::Plan;
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use crate::languages::common::GeneratorBase;
+use crate::templating::TemplateManifest;
+use log::info;
+use serde_json::Value;
+
+pub struct ExecutableGenerator {
+    base: GeneratorBase,
+}
+
+impl ExecutableGenerator {
+    pub fn new(config: &Config) -> Result<Self, ProjectError> {
+        let mut base = GeneratorBase::new(LANGUAGE, "executable", config)?;
+
+        base.load_type_templates(None)?;
+
+        Ok(ExecutableGenerator { base })
+    }
+}
+
+impl ProjectGenerator for ExecutableGenerator {
+    fn manifest(&self) -> &TemplateManifest {
+        self.base.manifest()
+    }
+
+    fn create_context(&self, project: &Project, config: &Config) -> Value {
+        self.base.create_context(project, config)
+    }
+
+    fn plan(&self, project: &Project, config: &Config) -> Result<Plan, ProjectError> {
+        info!("Planning C/C++ executable project: {}", project.name);
+
+        let plan = super::plan_files(&self.base, "executable", project, config)?;
+
+        info!("C/C++ executable project planned");
+        Ok(plan)
+    }
+}
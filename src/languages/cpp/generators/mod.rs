@@ -0,0 +1,51 @@
+pub mod executable;
+pub mod library;
+
+use crate::config::Config;
+use crate::core::plan::Plan;
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use crate::languages::common::GeneratorBase;
+use crate::utils::merge_contexts;
+use crate::utils::template_helpers::package_name;
+use serde_json::{json, Value};
+
+pub const LANGUAGE: &str = "cpp";
+
+// File extensions, the library target and whether a Meson build is written
+// follow from the answers, so they are added to the context here.
+pub fn plan_files(
+    base: &GeneratorBase,
+    project_type: &str,
+    project: &Project,
+    config: &Config,
+) -> Result<Plan, ProjectError> {
+    let cpp = project
+        .variables
+        .get("source_language")
+        .and_then(Value::as_str)
+        == Some("cpp");
+    let meson = project.variables.get("meson").and_then(Value::as_bool) == Some(true);
+    let executable = project_type == "executable";
+
+    // The executable takes the project's name, so its library gets a suffix
+    let library_target = if executable {
+        format!("{}_core", package_name(&project.name))
+    } else {
+        package_name(&project.name)
+    };
+
+    let build_ctx = json!({
+        "source_extension": if cpp { "cpp" } else { "c" },
+        "header_extension": if cpp { "hpp" } else { "h" },
+        "executable": executable,
+        "library_target": library_target,
+        "dependencies": config.get_project_type_config(project_type)?.dependencies,
+        "meson_file": if meson { "meson.build" } else { "" },
+    });
+    let context = merge_contexts(&[&base.create_context(project, config), &build_ctx]);
+
+    let mut plan = Plan::new();
+    base.render_files(&mut plan, &context)?;
+    Ok(plan)
+}
@@ -0,0 +1,80 @@
+pub mod generators;
+
+use crate::config::{unknown_project_type, Config};
+use crate::core::generator::ProjectGenerator;
+use crate::core::language::Language;
+use crate::core::plan::Plan;
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use log::info;
+
+const PROJECT_TYPES: &[&str] = &["executable", "library"];
+// The `cxx_standard` choices in templates/cpp/base/poq.toml
+const CXX_STANDARDS: &[&str] = &["11", "14", "17", "20", "23"];
+
+pub struct Cpp;
+
+impl Language for Cpp {
+    fn name(&self) -> &'static str {
+        "cpp"
+    }
+
+    // The version is the C++ standard
+    fn check_version(&self, version: &str) -> Result<(), String> {
+        if CXX_STANDARDS.contains(&version) {
+            Ok(())
+        } else {
+            Err(format!(
+                "'{}' is not a C++ standard (expected one of: {})",
+                version,
+                CXX_STANDARDS.join(", ")
+            ))
+        }
+    }
+
+    // A CMake package name for find_package()
+    fn check_dependency(&self, spec: &str) -> Result<(), String> {
+        let valid = spec.starts_with(|c: char| c.is_ascii_alphabetic())
+            && spec
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_.+-".contains(c));
+        if valid {
+            Ok(())
+        } else {
+            Err(format!("'{}' is not a CMake package name", spec))
+        }
+    }
+
+    fn test_frameworks(&self) -> &'static [&'static str] {
+        &["ctest"]
+    }
+
+    fn get_generator(
+        &self,
+        config: &Config,
+        project_type: &str,
+    ) -> Result<Box<dyn ProjectGenerator>, ProjectError> {
+        config.get_project_type_config(project_type)?;
+        let generator: Box<dyn ProjectGenerator> = match project_type {
+            "executable" => Box::new(generators::executable::ExecutableGenerator::new(config)?),
+            "library" => Box::new(generators::library::LibraryGenerator::new(config)?),
+            other => return Err(unknown_project_type(other, PROJECT_TYPES)),
+        };
+        Ok(generator)
+    }
+
+    // Configuring the build is left to the user, who may prefer Meson
+    fn setup_environment(&self, project: &Project, _config: &Config) -> Result<Plan, ProjectError> {
+        info!("Planning C/C++ environment for project: {}", project.name);
+        Ok(Plan::new())
+    }
+
+    fn next_steps(&self, project: &Project, _config: &Config) -> Vec<String> {
+        vec![
+            format!("cd {}", project.path.display()),
+            "cmake -S . -B build".to_string(),
+            "cmake --build build".to_string(),
+            "ctest --test-dir build".to_string(),
+        ]
+    }
+}
@@ -0,0 +1,43 @@
+use super::LANGUAGE;
+use crate::config::Config;
+use crate::core::generator::ProjectGenerator;
+use crate::core::plan::Plan;
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use crate::languages::common::GeneratorBase;
+use crate::templating::TemplateManifest;
+use log::info;
+use serde_json::Value;
+
+pub struct CliGenerator {
+    base: GeneratorBase,
+}
+
+impl CliGenerator {
+    pub fn new(config: &Config) -> Result<Self, ProjectError> {
+        let mut base = GeneratorBase::new(LANGUAGE, "cli", config)?;
+
+        base.load_type_templates(None)?;
+
+        Ok(CliGenerator { base })
+    }
+}
+
+impl ProjectGenerator for CliGenerator {
+    fn manifest(&self) -> &TemplateManifest {
+        self.base.manifest()
+    }
+
+    fn create_context(&self, project: &Project, config: &Config) -> Value {
+        self.base.create_context(project, config)
+    }
+
+    fn plan(&self, project: &Project, config: &Config) -> Result<Plan, ProjectError> {
+        info!("Planning Go CLI project: {}", project.name);
+
+        let plan = super::plan_files(&self.base, "cli", project, config)?;
+
+        info!("Go CLI project planned");
+        Ok(plan)
+    }
+}
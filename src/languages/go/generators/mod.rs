@@ -0,0 +1,49 @@
+pub mod cli;
+pub mod module;
+pub mod service;
+
+use crate::config::Config;
+use crate::core::plan::Plan;
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use crate::languages::common::GeneratorBase;
+use crate::utils::merge_contexts;
+use serde_json::json;
+
+pub const LANGUAGE: &str = "go";
+
+pub fn plan_files(
+    base: &GeneratorBase,
+    project_type: &str,
+    project: &Project,
+    config: &Config,
+) -> Result<Plan, ProjectError> {
+    let requirements =
+        requirement_lines(&config.get_project_type_config(project_type)?.dependencies)?;
+    let context = merge_contexts(&[
+        &base.create_context(project, config),
+        &json!({ "requirements": requirements }),
+    ]);
+
+    let mut plan = Plan::new();
+    base.render_files(&mut plan, &context)?;
+    Ok(plan)
+}
+
+// `github.com/spf13/cobra@v1.8.1` becomes `github.com/spf13/cobra v1.8.1`
+pub fn requirement_lines(dependencies: &[String]) -> Result<Vec<String>, ProjectError> {
+    dependencies
+        .iter()
+        .map(|spec| {
+            spec.rsplit_once('@')
+                .filter(|(path, version)| !path.is_empty() && version.starts_with('v'))
+                .map(|(path, version)| format!("{} {}", path, version))
+                .ok_or_else(|| {
+                    ProjectError::Config(format!(
+                        "Invalid Go dependency '{}': expected path@vX.Y.Z",
+                        spec
+                    ))
+                })
+        })
+        .collect()
+}
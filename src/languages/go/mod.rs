@@ -0,0 +1,83 @@
+pub mod generators;
+
+use crate::config::{unknown_project_type, Config};
+use crate::core::generator::ProjectGenerator;
+use crate::core::language::Language;
+use crate::core::plan::{CommandSpec, Plan};
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use crate::utils::find_executable;
+use log::{info, warn};
+
+const PROJECT_TYPES: &[&str] = &["module", "cli", "service"];
+
+pub struct Go;
+
+impl Language for Go {
+    fn name(&self) -> &'static str {
+        "go"
+    }
+
+    fn check_version(&self, version: &str) -> Result<(), String> {
+        let parts: Vec<&str> = version.split('.').collect();
+        let numeric = parts
+            .iter()
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+        if numeric && parts[0] == "1" && (2..=3).contains(&parts.len()) {
+            Ok(())
+        } else {
+            Err(format!(
+                "'{}' is not a Go version (expected e.g. 1.22)",
+                version
+            ))
+        }
+    }
+
+    fn check_dependency(&self, spec: &str) -> Result<(), String> {
+        generators::requirement_lines(&[spec.to_string()])
+            .map(|_| ())
+            .map_err(|e| e.detail())
+    }
+
+    fn test_frameworks(&self) -> &'static [&'static str] {
+        &["testing"]
+    }
+
+    fn get_generator(
+        &self,
+        config: &Config,
+        project_type: &str,
+    ) -> Result<Box<dyn ProjectGenerator>, ProjectError> {
+        config.get_project_type_config(project_type)?;
+        let generator: Box<dyn ProjectGenerator> = match project_type {
+            "cli" => Box::new(generators::cli::CliGenerator::new(config)?),
+            "service" => Box::new(generators::service::ServiceGenerator::new(config)?),
+            "module" => Box::new(generators::module::ModuleGenerator::new(config)?),
+            other => return Err(unknown_project_type(other, PROJECT_TYPES)),
+        };
+        Ok(generator)
+    }
+
+    fn setup_environment(&self, project: &Project, _config: &Config) -> Result<Plan, ProjectError> {
+        info!("Planning Go environment for project: {}", project.name);
+
+        let mut plan = Plan::new();
+        if find_executable("go").is_some() {
+            plan.run_command(
+                CommandSpec::new("go", &["mod", "tidy"], "resolve module dependencies").optional(),
+            );
+        } else {
+            warn!("`go` was not found on PATH; skipping `go mod tidy`");
+        }
+        Ok(plan)
+    }
+
+    fn next_steps(&self, project: &Project, _config: &Config) -> Vec<String> {
+        vec![
+            format!("cd {}", project.path.display()),
+            "go mod tidy".to_string(),
+            "go build ./...".to_string(),
+            "go test ./...".to_string(),
+        ]
+    }
+}
@@ -0,0 +1,43 @@
+use super::LANGUAGE;
+use crate::config::Config;
+use crate::core::generator::ProjectGenerator;
+use crate::core::plan::Plan;
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use crate::languages::common::GeneratorBase;
+use crate::templating::TemplateManifest;
+use log::info;
+use serde_json::Value;
+
+pub struct ApplicationGenerator {
+    base: GeneratorBase,
+}
+
+impl ApplicationGenerator {
+    pub fn new(config: &Config) -> Result<Self, ProjectError> {
+        let mut base = GeneratorBase::new(LANGUAGE, "application", config)?;
+
+        base.load_type_templates(None)?;
+
+        Ok(ApplicationGenerator { base })
+    }
+}
+
+impl ProjectGenerator for ApplicationGenerator {
+    fn manifest(&self) -> &TemplateManifest {
+        self.base.manifest()
+    }
+
+    fn create_context(&self, project: &Project, config: &Config) -> Value {
+        self.base.create_context(project, config)
+    }
+
+    fn plan(&self, project: &Project, config: &Config) -> Result<Plan, ProjectError> {
+        info!("Planning JVM application project: {}", project.name);
+
+        let plan = super::plan_files(&self.base, "application", project, config)?;
+
+        info!("JVM application project planned");
+        Ok(plan)
+    }
+}
@@ -0,0 +1,71 @@
+pub mod application;
+pub mod library;
+
+use crate::config::Config;
+use crate::core::plan::Plan;
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use crate::languages::common::GeneratorBase;
+use crate::utils::merge_contexts;
+use serde_json::{json, Value};
+
+pub const LANGUAGE: &str = "jvm";
+
+// Which build files exist, where sources go and the main class all follow
+// from the answers, so they are added to the context here.
+pub fn plan_files(
+    base: &GeneratorBase,
+    project_type: &str,
+    project: &Project,
+    config: &Config,
+) -> Result<Plan, ProjectError> {
+    let answer = |key: &str| project.variables.get(key).and_then(Value::as_str);
+    let kotlin = answer("jvm_language") == Some("kotlin");
+    let gradle = answer("build_tool") == Some("gradle");
+    let package = answer("package").unwrap_or_default();
+
+    let main_class = match (project_type, kotlin) {
+        ("application", true) => format!("{}.AppKt", package),
+        ("application", false) => format!("{}.App", package),
+        _ => String::new(),
+    };
+    let dependencies =
+        dependency_coordinates(&config.get_project_type_config(project_type)?.dependencies)?;
+
+    let build_ctx = json!({
+        "package_path": package_path(package),
+        "source_extension": if kotlin { "kt" } else { "java" },
+        "main_class": main_class,
+        "dependencies": dependencies,
+        "pom_file": if gradle { "" } else { "pom.xml" },
+        "gradle_build_file": if gradle { "build.gradle.kts" } else { "" },
+        "gradle_settings_file": if gradle { "settings.gradle.kts" } else { "" },
+    });
+    let context = merge_contexts(&[&base.create_context(project, config), &build_ctx]);
+
+    let mut plan = Plan::new();
+    base.render_files(&mut plan, &context)?;
+    Ok(plan)
+}
+
+// `com.acme.app` lives in `com/acme/app`
+pub fn package_path(package: &str) -> String {
+    package.replace('.', "/")
+}
+
+pub fn dependency_coordinates(dependencies: &[String]) -> Result<Vec<Value>, ProjectError> {
+    dependencies
+        .iter()
+        .map(|spec| match spec.split(':').collect::<Vec<_>>()[..] {
+            [group, artifact, version]
+                if !group.is_empty() && !artifact.is_empty() && !version.is_empty() =>
+            {
+                Ok(json!({ "group": group, "artifact": artifact, "version": version }))
+            }
+            _ => Err(ProjectError::Config(format!(
+                "Invalid Maven dependency '{}': expected group:artifact:version",
+                spec
+            ))),
+        })
+        .collect()
+}
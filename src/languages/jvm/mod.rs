@@ -0,0 +1,78 @@
+pub mod generators;
+
+use crate::config::{unknown_project_type, Config};
+use crate::core::generator::ProjectGenerator;
+use crate::core::language::Language;
+use crate::core::plan::Plan;
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use log::info;
+
+const PROJECT_TYPES: &[&str] = &["application", "library"];
+
+pub struct Jvm;
+
+impl Language for Jvm {
+    fn name(&self) -> &'static str {
+        "jvm"
+    }
+
+    // The Java release, e.g. 17 (or 1.8)
+    fn check_version(&self, version: &str) -> Result<(), String> {
+        if !version.is_empty() && version.bytes().all(|b| b.is_ascii_digit()) {
+            Ok(())
+        } else if let Some(release) = version.strip_prefix("1.") {
+            Err(format!(
+                "'{}' is not a Java release (write it as '{}')",
+                version, release
+            ))
+        } else {
+            Err(format!(
+                "'{}' is not a Java release (expected e.g. 17)",
+                version
+            ))
+        }
+    }
+
+    fn check_dependency(&self, spec: &str) -> Result<(), String> {
+        generators::dependency_coordinates(&[spec.to_string()])
+            .map(|_| ())
+            .map_err(|e| e.detail())
+    }
+
+    fn test_frameworks(&self) -> &'static [&'static str] {
+        &["junit5"]
+    }
+
+    fn get_generator(
+        &self,
+        config: &Config,
+        project_type: &str,
+    ) -> Result<Box<dyn ProjectGenerator>, ProjectError> {
+        config.get_project_type_config(project_type)?;
+        let generator: Box<dyn ProjectGenerator> = match project_type {
+            "application" => Box::new(generators::application::ApplicationGenerator::new(config)?),
+            "library" => Box::new(generators::library::LibraryGenerator::new(config)?),
+            other => return Err(unknown_project_type(other, PROJECT_TYPES)),
+        };
+        Ok(generator)
+    }
+
+    // Maven and Gradle download dependencies on the first build
+    fn setup_environment(&self, project: &Project, _config: &Config) -> Result<Plan, ProjectError> {
+        info!("Planning JVM environment for project: {}", project.name);
+        Ok(Plan::new())
+    }
+
+    fn next_steps(&self, project: &Project, _config: &Config) -> Vec<String> {
+        let gradle = project
+            .variables
+            .get("build_tool")
+            .and_then(|tool| tool.as_str())
+            == Some("gradle");
+        vec![
+            format!("cd {}", project.path.display()),
+            if gradle { "gradle test" } else { "mvn test" }.to_string(),
+        ]
+    }
+}
@@ -1,18 +1,32 @@
+pub mod common;
+pub mod cpp;
+pub mod go;
+pub mod jvm;
+pub mod node;
 pub mod python;
-// pub mod go;  // Uncomment when Go is implemented
+pub mod rust;
 
 use crate::core::language::Language;
 
+pub use cpp::Cpp;
+pub use go::Go;
+pub use jvm::Jvm;
+pub use node::Node;
 pub use python::Python;
+pub use rust::Rust;
 
 pub fn get_language(name: &str) -> Option<Box<dyn Language>> {
     match name.to_lowercase().as_str() {
         "python" | "py" => Some(Box::new(Python)),
-        // "go" | "golang" => Some(Box::new(go::Go)),
+        "go" | "golang" => Some(Box::new(Go)),
+        "rust" | "rs" => Some(Box::new(Rust)),
+        "node" | "nodejs" | "typescript" | "ts" => Some(Box::new(Node)),
+        "jvm" | "java" | "kotlin" => Some(Box::new(Jvm)),
+        "cpp" | "c++" | "cxx" | "c" => Some(Box::new(Cpp)),
         _ => None,
     }
 }
 
 pub fn supported_languages() -> Vec<&'static str> {
-    vec!["python"] // Add "go" when implemented
+    vec!["python", "go", "rust", "node", "jvm", "cpp"]
 }
@@ -0,0 +1,43 @@
+use super::LANGUAGE;
+use crate::config::Config;
+use crate::core::generator::ProjectGenerator;
+use crate::core::plan::Plan;
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use crate::languages::common::GeneratorBase;
+use crate::templating::TemplateManifest;
+use log::info;
+use serde_json::Value;
+
+pub struct LibraryGenerator {
+    base: GeneratorBase,
+}
+
+impl LibraryGenerator {
+    pub fn new(config: &Config) -> Result<Self, ProjectError> {
+        let mut base = GeneratorBase::new(LANGUAGE, "library", config)?;
+
+        base.load_type_templates(None)?;
+
+        Ok(LibraryGenerator { base })
+    }
+}
+
+impl ProjectGenerator for LibraryGenerator {
+    fn manifest(&self) -> &TemplateManifest {
+        self.base.manifest()
+    }
+
+    fn create_context(&self, project: &Project, config: &Config) -> Value {
+        super::create_context(&self.base, project, config)
+    }
+
+    fn plan(&self, project: &Project, config: &Config) -> Result<Plan, ProjectError> {
+        info!("Planning Node library project: {}", project.name);
+
+        let plan = super::plan_files(&self.base, "library", project, config)?;
+
+        info!("Node library project planned");
+        Ok(plan)
+    }
+}
@@ -0,0 +1,122 @@
+pub mod cli;
+pub mod library;
+pub mod service;
+
+use crate::config::Config;
+use crate::core::plan::Plan;
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use crate::languages::common::GeneratorBase;
+use crate::utils::merge_contexts;
+use serde_json::{json, Map, Value};
+
+pub const LANGUAGE: &str = "node";
+const TYPESCRIPT: &str = "typescript@^5.6.0";
+
+pub fn create_context(base: &GeneratorBase, project: &Project, config: &Config) -> Value {
+    let node_ctx = json!({
+        "default_test_framework": config.test.framework,
+        "package_manager": super::package_manager(config),
+    });
+    merge_contexts(&[&base.create_context(project, config), &node_ctx])
+}
+
+// package.json's dependency tables and scripts depend on the answers, so
+// they are built here and spliced into the template as JSON.
+pub fn plan_files(
+    base: &GeneratorBase,
+    project_type: &str,
+    project: &Project,
+    config: &Config,
+) -> Result<Plan, ProjectError> {
+    let answer = |key: &str| project.variables.get(key).and_then(Value::as_str);
+    let test_framework = answer("test_framework").unwrap_or(&config.test.framework);
+    let node_version = answer("node_version").unwrap_or(&config.general.version);
+
+    let mut dependencies = config
+        .get_project_type_config(project_type)?
+        .dependencies
+        .clone();
+    let mut dev_dependencies = vec![
+        TYPESCRIPT.to_string(),
+        format!("@types/node@^{}", node_version),
+    ];
+    match test_framework {
+        "vitest" => dev_dependencies.push("vitest@^2.1.0".to_string()),
+        "jest" => dev_dependencies
+            .extend(["jest@^29.7.0", "ts-jest@^29.2.0", "@types/jest@^29.5.0"].map(String::from)),
+        other => {
+            return Err(ProjectError::Config(format!(
+                "Unsupported test framework for Node: {}",
+                other
+            )))
+        }
+    }
+    match answer("web_framework") {
+        Some("express") => {
+            dependencies.push("express@^4.21.0".to_string());
+            dev_dependencies.push("@types/express@^4.17.21".to_string());
+        }
+        Some("fastify") => dependencies.push("fastify@^5.0.0".to_string()),
+        _ => {}
+    }
+
+    let mut scripts = Map::new();
+    scripts.insert("build".to_string(), json!("tsc"));
+    scripts.insert(
+        "test".to_string(),
+        json!(if test_framework == "jest" {
+            "jest"
+        } else {
+            "vitest run"
+        }),
+    );
+    if project_type != "library" {
+        scripts.insert("start".to_string(), json!("node dist/index.js"));
+    }
+    let bin = if project_type == "cli" {
+        json_block(&json!({ project.name.to_lowercase(): "dist/index.js" }))
+    } else {
+        String::new()
+    };
+
+    let package_ctx = json!({
+        "package_bin": bin,
+        "package_scripts": json_block(&Value::Object(scripts)),
+        "package_dependencies": json_block(&dependency_table(&dependencies)?),
+        "package_dev_dependencies": json_block(&dependency_table(&dev_dependencies)?),
+        "jest_config_file": if test_framework == "jest" { "jest.config.js" } else { "" },
+    });
+    let context = merge_contexts(&[&create_context(base, project, config), &package_ctx]);
+
+    let mut plan = Plan::new();
+    base.render_files(&mut plan, &context)?;
+    Ok(plan)
+}
+
+// `@types/node@^20` becomes `"@types/node": "^20"`; the leading `@` of a
+// scoped package is part of the name.
+pub fn dependency_table(dependencies: &[String]) -> Result<Value, ProjectError> {
+    let mut table = Map::new();
+    for spec in dependencies {
+        let (name, range) = spec
+            .rfind('@')
+            .filter(|&at| at > 0 && at + 1 < spec.len())
+            .map(|at| (&spec[..at], &spec[at + 1..]))
+            .ok_or_else(|| {
+                ProjectError::Config(format!(
+                    "Invalid npm dependency '{}': expected name@range",
+                    spec
+                ))
+            })?;
+        table.insert(name.to_string(), json!(range));
+    }
+    Ok(Value::Object(table))
+}
+
+// Pretty-printed and indented to sit at the second level of package.json
+fn json_block(value: &Value) -> String {
+    serde_json::to_string_pretty(value)
+        .unwrap_or_default()
+        .replace('\n', "\n  ")
+}
@@ -0,0 +1,190 @@
+pub mod generators;
+
+use crate::config::{unknown_project_type, unknown_setting, Config};
+use crate::core::generator::ProjectGenerator;
+use crate::core::language::Language;
+use crate::core::plan::{CommandSpec, Plan};
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use crate::utils::find_executable;
+use log::{info, warn};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+const DEFAULT_REGISTRY: &str = "https://registry.npmjs.org/";
+const REGISTRY_TIMEOUT: Duration = Duration::from_secs(3);
+
+const PROJECT_TYPES: &[&str] = &["library", "cli", "service"];
+const SETTINGS: &[&str] = &["package_manager", "registry"];
+pub const PACKAGE_MANAGERS: &[&str] = &["npm", "pnpm", "yarn"];
+
+pub struct Node;
+
+impl Language for Node {
+    fn name(&self) -> &'static str {
+        "node"
+    }
+
+    fn check_version(&self, version: &str) -> Result<(), String> {
+        if !version.is_empty() && version.bytes().all(|b| b.is_ascii_digit()) {
+            Ok(())
+        } else {
+            Err(format!(
+                "'{}' is not a Node major version (expected e.g. 20)",
+                version
+            ))
+        }
+    }
+
+    fn check_dependency(&self, spec: &str) -> Result<(), String> {
+        generators::dependency_table(&[spec.to_string()])
+            .map(|_| ())
+            .map_err(|e| e.detail())
+    }
+
+    fn test_frameworks(&self) -> &'static [&'static str] {
+        &["vitest", "jest"]
+    }
+
+    fn check_setting(&self, key: &str, value: &toml::Value) -> Result<(), String> {
+        match key {
+            "package_manager" => match value.as_str() {
+                Some(manager) if PACKAGE_MANAGERS.contains(&manager) => Ok(()),
+                _ => Err(format!(
+                    "unsupported package manager {} (expected one of: {})",
+                    value,
+                    PACKAGE_MANAGERS.join(", ")
+                )),
+            },
+            "registry" => match value.as_str() {
+                Some(url) if url.contains("://") => Ok(()),
+                _ => Err(format!("expected a URL, got {}", value)),
+            },
+            _ => Err(unknown_setting(key, SETTINGS)),
+        }
+    }
+
+    fn get_generator(
+        &self,
+        config: &Config,
+        project_type: &str,
+    ) -> Result<Box<dyn ProjectGenerator>, ProjectError> {
+        config.get_project_type_config(project_type)?;
+        let generator: Box<dyn ProjectGenerator> = match project_type {
+            "cli" => Box::new(generators::cli::CliGenerator::new(config)?),
+            "service" => Box::new(generators::service::ServiceGenerator::new(config)?),
+            "library" => Box::new(generators::library::LibraryGenerator::new(config)?),
+            other => return Err(unknown_project_type(other, PROJECT_TYPES)),
+        };
+        Ok(generator)
+    }
+
+    // Installing needs both the package manager and the network, so both are
+    // checked up front and a missing one skips the install (recorded as
+    // `[settings] install = false`).
+    fn resolve_toolchain(&self, config: &mut Config) -> Result<(), ProjectError> {
+        let package_manager = package_manager(config).to_string();
+        let install = if find_executable(&package_manager).is_none() {
+            warn!(
+                "`{}` was not found on PATH; skipping dependency installation",
+                package_manager
+            );
+            false
+        } else {
+            let registry = config
+                .setting("registry")
+                .and_then(|v| v.as_str())
+                .map(String::from)
+                .unwrap_or_else(|| npm_registry(&package_manager));
+            let reachable = registry_reachable(&registry);
+            if !reachable {
+                warn!(
+                    "{} is unreachable; skipping dependency installation",
+                    registry
+                );
+            }
+            reachable
+        };
+        config
+            .settings
+            .insert("install".to_string(), toml::Value::Boolean(install));
+        Ok(())
+    }
+
+    // A failed install is reported but doesn't undo the generated project.
+    fn setup_environment(&self, project: &Project, config: &Config) -> Result<Plan, ProjectError> {
+        info!("Planning Node environment for project: {}", project.name);
+
+        let package_manager = package_manager(config);
+        if !PACKAGE_MANAGERS.contains(&package_manager) {
+            return Err(ProjectError::Config(format!(
+                "Unsupported package manager '{}' (expected one of: {})",
+                package_manager,
+                PACKAGE_MANAGERS.join(", ")
+            )));
+        }
+
+        let mut plan = Plan::new();
+        if config.setting("install").and_then(|v| v.as_bool()) != Some(false) {
+            let registry = config.setting("registry").and_then(|v| v.as_str());
+            plan.run_command(
+                CommandSpec::new(package_manager, &["install"], "install dependencies")
+                    .with_args(registry.map(|url| format!("--registry={}", url)))
+                    .optional(),
+            );
+        }
+        Ok(plan)
+    }
+
+    fn next_steps(&self, project: &Project, config: &Config) -> Vec<String> {
+        let package_manager = package_manager(config);
+        vec![
+            format!("cd {}", project.path.display()),
+            format!("{} install", package_manager),
+            format!("{} run build", package_manager),
+            format!("{} test", package_manager),
+        ]
+    }
+}
+
+// `[settings] package_manager` in the Node config
+pub fn package_manager(config: &Config) -> &str {
+    config
+        .setting("package_manager")
+        .and_then(|v| v.as_str())
+        .unwrap_or("npm")
+}
+
+// The registry the package manager would use, from .npmrc files and
+// `npm_config_*` variables
+fn npm_registry(package_manager: &str) -> String {
+    Command::new(package_manager)
+        .args(["config", "get", "registry"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|url| url.contains("://"))
+        .unwrap_or_else(|| DEFAULT_REGISTRY.to_string())
+}
+
+// Package managers retry for minutes when offline, so check up front
+fn registry_reachable(url: &str) -> bool {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("https", url));
+    let authority = rest.split('/').next().unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    let address = if host.contains(':') {
+        host.to_string()
+    } else {
+        let port = if scheme == "http" { 80 } else { 443 };
+        format!("{}:{}", host, port)
+    };
+    address
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .is_some_and(|addr| TcpStream::connect_timeout(&addr, REGISTRY_TIMEOUT).is_ok())
+}
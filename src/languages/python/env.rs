@@ -0,0 +1,348 @@
+use crate::config::Config;
+use crate::core::plan::CommandSpec;
+use crate::error::ProjectError;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+pub const ENV_BACKENDS: &[&str] = &["venv", "virtualenv", "uv", "conda", "poetry", "none"];
+const DEFAULT_BACKEND: &str = "venv";
+const VENV_DIR: &str = "venv";
+const CONDA_PREFIX: &str = ".conda";
+// Where the org constraints file is copied in the project
+pub const CONSTRAINTS_FILE: &str = "constraints.txt";
+
+// Where packages are installed from instead of PyPI: `[settings] find_links`
+// (a local wheel directory) and `index_url` (a mirror). A wheel directory on
+// its own means installing offline. `constraints` is a pip constraints file
+// limiting the versions installed from them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageSources {
+    pub find_links: Option<PathBuf>,
+    pub index_url: Option<String>,
+    pub constraints: Option<PathBuf>,
+}
+
+impl PackageSources {
+    // Relative paths are taken from the current directory, since commands
+    // run inside the project
+    pub fn from_config(config: &Config) -> Result<Self, ProjectError> {
+        let setting = |key: &str| {
+            config
+                .setting(key)
+                .and_then(|v| v.as_str())
+                .filter(|v| !v.is_empty())
+                .map(String::from)
+        };
+        let find_links = setting("find_links").map(std::path::absolute).transpose()?;
+        let constraints = setting("constraints")
+            .map(std::path::absolute)
+            .transpose()?;
+        Ok(Self {
+            find_links,
+            index_url: setting("index_url"),
+            constraints,
+        })
+    }
+
+    // pip and uv take the same options
+    pub fn install_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        match &self.index_url {
+            Some(url) => args.extend(["--index-url".to_string(), url.clone()]),
+            None if self.find_links.is_some() => args.push("--no-index".to_string()),
+            None => {}
+        }
+        if let Some(dir) = &self.find_links {
+            args.extend(["--find-links".to_string(), dir.display().to_string()]);
+        }
+        args
+    }
+
+    // For pip run outside the project, which has its own copy
+    pub fn constraint_args(&self) -> Vec<String> {
+        self.constraints
+            .iter()
+            .flat_map(|path| ["-c".to_string(), path.display().to_string()])
+            .collect()
+    }
+
+    // For pip run by another tool, e.g. conda's `pip:` dependencies
+    fn pip_env(&self, mut command: CommandSpec) -> CommandSpec {
+        match &self.index_url {
+            Some(url) => command = command.with_env("PIP_INDEX_URL", url.clone()),
+            None if self.find_links.is_some() => command = command.with_env("PIP_NO_INDEX", "1"),
+            None => {}
+        }
+        if let Some(dir) = &self.find_links {
+            command = command.with_env("PIP_FIND_LINKS", dir.display().to_string());
+        }
+        command
+    }
+}
+
+// Who creates the project's environment and installs its dependencies.
+// Backend files (e.g. conda's `environment.yml`) come from the
+// `python/env/<backend>` templates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvBackend {
+    Venv,
+    Virtualenv,
+    Uv,
+    Conda,
+    Poetry,
+    None,
+}
+
+impl FromStr for EnvBackend {
+    type Err = ProjectError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "venv" => Ok(EnvBackend::Venv),
+            "virtualenv" => Ok(EnvBackend::Virtualenv),
+            "uv" => Ok(EnvBackend::Uv),
+            "conda" => Ok(EnvBackend::Conda),
+            "poetry" => Ok(EnvBackend::Poetry),
+            "none" => Ok(EnvBackend::None),
+            other => Err(ProjectError::Config(format!(
+                "Unknown Python environment '{}' (expected one of: {})",
+                other,
+                ENV_BACKENDS.join(", ")
+            ))),
+        }
+    }
+}
+
+impl EnvBackend {
+    // `[settings] env` in the Python config
+    pub fn from_config(config: &Config) -> Result<Self, ProjectError> {
+        config
+            .setting("env")
+            .and_then(|v| v.as_str())
+            .unwrap_or(DEFAULT_BACKEND)
+            .parse()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EnvBackend::Venv => "venv",
+            EnvBackend::Virtualenv => "virtualenv",
+            EnvBackend::Uv => "uv",
+            EnvBackend::Conda => "conda",
+            EnvBackend::Poetry => "poetry",
+            EnvBackend::None => "none",
+        }
+    }
+
+    // uv and poetry read dependencies from pyproject.toml instead
+    pub fn uses_requirements_file(self) -> bool {
+        !matches!(self, EnvBackend::Uv | EnvBackend::Poetry)
+    }
+
+    pub fn uses_interpreter(self) -> bool {
+        !matches!(self, EnvBackend::Conda | EnvBackend::None)
+    }
+
+    pub fn run_command(self, script: &str) -> String {
+        match self {
+            EnvBackend::Uv => format!("uv run python {}", script),
+            EnvBackend::Poetry => format!("poetry run python {}", script),
+            EnvBackend::Conda => format!("conda run --prefix {} python {}", CONDA_PREFIX, script),
+            EnvBackend::Venv | EnvBackend::Virtualenv => {
+                format!("{}/bin/python {}", VENV_DIR, script)
+            }
+            EnvBackend::None => format!("python {}", script),
+        }
+    }
+}
+
+// Everything about how a project's environment is set up, from the config
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Environment {
+    pub backend: EnvBackend,
+    pub sources: PackageSources,
+    // Found by `Python::resolve_toolchain`; without it `python` from PATH
+    pub interpreter: Option<PathBuf>,
+    // Installed from pyproject.toml (`[settings] pyproject`) rather than
+    // requirements.txt
+    pub packaged: bool,
+}
+
+impl Environment {
+    pub fn from_config(config: &Config) -> Result<Self, ProjectError> {
+        Ok(Self {
+            backend: EnvBackend::from_config(config)?,
+            sources: PackageSources::from_config(config)?,
+            interpreter: config
+                .setting("interpreter")
+                .and_then(|v| v.as_str())
+                .map(PathBuf::from),
+            packaged: config
+                .setting("pyproject")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        })
+    }
+
+    pub fn writes_requirements(&self) -> bool {
+        self.backend.uses_requirements_file() && !self.packaged
+    }
+
+    pub fn writes_pyproject(&self) -> bool {
+        self.packaged || !self.backend.uses_requirements_file()
+    }
+
+    // A development environment: the project itself with its dev extra, or
+    // requirements-dev.txt, which includes requirements.txt and with it the
+    // constraints
+    fn pip_install_args(&self) -> Vec<&'static str> {
+        let mut args = if self.packaged {
+            vec!["install", "-e", ".[dev]"]
+        } else {
+            vec!["install", "-r", "requirements-dev.txt"]
+        };
+        if self.packaged && self.sources.constraints.is_some() {
+            args.extend(["-c", CONSTRAINTS_FILE]);
+        }
+        args
+    }
+
+    pub fn commands(&self) -> Result<Vec<CommandSpec>, ProjectError> {
+        let python = self
+            .interpreter
+            .as_ref()
+            .map(|path| path.display().to_string());
+        let pip_install = CommandSpec::new(
+            format!("{}/bin/pip", VENV_DIR),
+            &self.pip_install_args(),
+            "install dependencies",
+        )
+        .with_args(self.sources.install_args());
+        Ok(match self.backend {
+            EnvBackend::Venv => vec![
+                CommandSpec::new(
+                    python.as_deref().unwrap_or("python"),
+                    &["-m", "venv", VENV_DIR],
+                    "create virtual environment",
+                ),
+                pip_install,
+            ],
+            EnvBackend::Virtualenv => vec![
+                CommandSpec::new("virtualenv", &[], "create virtual environment")
+                    .with_args(python.map(|python| format!("--python={}", python)))
+                    .with_args([VENV_DIR]),
+                pip_install,
+            ],
+            // Creates .venv and writes uv.lock
+            EnvBackend::Uv => {
+                vec![
+                    CommandSpec::new("uv", &["sync"], "create environment and lock dependencies")
+                        .with_args(python.map(|python| format!("--python={}", python)))
+                        .with_args(self.sources.install_args()),
+                ]
+            }
+            // Only the pip dependencies follow the sources; conda's own
+            // packages need a reachable or local channel
+            EnvBackend::Conda => vec![self.sources.pip_env(CommandSpec::new(
+                "conda",
+                &[
+                    "env",
+                    "create",
+                    "--file",
+                    "environment.yml",
+                    "--prefix",
+                    CONDA_PREFIX,
+                ],
+                "create conda environment",
+            ))],
+            // poetry.toml keeps the environment in the project; a mirror is a
+            // package source in pyproject.toml
+            EnvBackend::Poetry => {
+                if let Some(dir) = &self.sources.find_links {
+                    return Err(ProjectError::Config(format!(
+                        "The poetry environment can't install from a wheel directory ('{}'); use index_url or another environment",
+                        dir.display()
+                    )));
+                }
+                if let Some(file) = &self.sources.constraints {
+                    return Err(ProjectError::Config(format!(
+                        "The poetry environment can't apply a constraints file ('{}'); use another environment",
+                        file.display()
+                    )));
+                }
+                let mut commands: Vec<CommandSpec> = python
+                    .iter()
+                    .map(|python| {
+                        CommandSpec::new("poetry", &["env", "use"], "select the interpreter")
+                            .with_args([python.clone()])
+                    })
+                    .collect();
+                commands.push(CommandSpec::new(
+                    "poetry",
+                    &["install"],
+                    "create environment and lock dependencies",
+                ));
+                commands
+            }
+            EnvBackend::None => Vec::new(),
+        })
+    }
+
+    // What a user runs to set the environment up by hand, for the README
+    pub fn setup_steps(&self) -> Vec<String> {
+        // Quoted so that shells don't expand the brackets
+        let pip_install = match (self.packaged, &self.sources.constraints) {
+            (true, Some(_)) => format!("pip install -e \".[dev]\" -c {}", CONSTRAINTS_FILE),
+            (true, None) => "pip install -e \".[dev]\"".to_string(),
+            (false, _) => "pip install -r requirements-dev.txt".to_string(),
+        };
+        match self.backend {
+            EnvBackend::Venv => vec![
+                "python -m venv venv".to_string(),
+                "source venv/bin/activate".to_string(),
+                pip_install,
+            ],
+            EnvBackend::Virtualenv => vec![
+                "virtualenv venv".to_string(),
+                "source venv/bin/activate".to_string(),
+                pip_install,
+            ],
+            EnvBackend::Uv => vec!["uv sync".to_string()],
+            EnvBackend::Conda => vec![
+                "conda env create --file environment.yml --prefix .conda".to_string(),
+                "conda activate ./.conda".to_string(),
+            ],
+            EnvBackend::Poetry => vec!["poetry install".to_string()],
+            EnvBackend::None => vec![pip_install],
+        }
+    }
+}
+
+// `pip download` of the given requirements into `dest` with `python`, run on
+// a connected machine to fill the wheel directory used by `find_links`. The
+// index is never turned off: the wheels come from it.
+pub fn download_command(
+    python: &str,
+    requirements: &[String],
+    sources: &PackageSources,
+    dest: &Path,
+) -> CommandSpec {
+    let index_url = sources
+        .index_url
+        .iter()
+        .flat_map(|url| ["--index-url".to_string(), url.clone()]);
+    let find_links = sources
+        .find_links
+        .iter()
+        .flat_map(|dir| ["--find-links".to_string(), dir.display().to_string()]);
+    CommandSpec::new(
+        python,
+        &["-m", "pip", "download", "--dest"],
+        "download packages",
+    )
+    .with_args([dest.display().to_string()])
+    .with_args(index_url)
+    .with_args(find_links)
+    .with_args(sources.constraint_args())
+    .with_args(requirements.iter().cloned())
+}
@@ -1,47 +1,63 @@
-use super::common::GeneratorBase;
+use super::common::{load_env_templates, plan_files, GeneratorBase, LANGUAGE};
 use crate::config::Config;
 use crate::core::generator::ProjectGenerator;
+use crate::core::plan::Plan;
 use crate::core::project::Project;
 use crate::error::ProjectError;
-use log::{error, info};
+use crate::templating::TemplateManifest;
+use log::{debug, info};
+use serde_json::Value;
 
+// Also serves project types that exist only in the config: their templates,
+// if any, are layered over the base tree.
 pub struct BaseGenerator {
     base: GeneratorBase,
+    project_type: String,
 }
 
-impl Default for BaseGenerator {
-    fn default() -> Self {
-        Self::new()
+impl BaseGenerator {
+    pub fn new(config: &Config) -> Result<Self, ProjectError> {
+        Self::for_type(config, "base")
     }
-}
 
-impl BaseGenerator {
-    pub fn new() -> Self {
-        let mut base = match GeneratorBase::new("base") {
-            Ok(b) => b,
-            Err(e) => {
-                error!("Failed to create generator base: {}", e);
-                GeneratorBase::new("base").unwrap_or_else(|_| panic!("Cannot create generator"))
-            }
-        };
+    pub fn for_type(config: &Config, project_type: &str) -> Result<Self, ProjectError> {
+        let mut base = GeneratorBase::new(LANGUAGE, project_type, config)?;
 
-        if let Err(e) = base.load_main_template("main.py.template") {
-            error!("Failed to load main template: {}", e);
+        if project_type != "base" {
+            if base.has_type_templates() {
+                base.load_type_templates(None)?;
+            } else {
+                debug!("No templates for project type '{}'", project_type);
+            }
         }
 
-        BaseGenerator { base }
+        load_env_templates(&mut base, config)?;
+
+        Ok(BaseGenerator {
+            base,
+            project_type: project_type.to_string(),
+        })
     }
 }
 
 impl ProjectGenerator for BaseGenerator {
-    fn generate(&self, project: &Project, config: &Config) -> Result<(), ProjectError> {
-        info!("Generating base Python project: {}", project.name);
+    fn manifest(&self) -> &TemplateManifest {
+        self.base.manifest()
+    }
+
+    fn create_context(&self, project: &Project, config: &Config) -> Value {
+        self.base.create_context(project, config)
+    }
+
+    fn plan(&self, project: &Project, config: &Config) -> Result<Plan, ProjectError> {
+        info!(
+            "Planning {} Python project: {}",
+            self.project_type, project.name
+        );
 
-        self.base.create_project_structure(project)?;
-        self.base.generate_base_files(project, config)?;
-        self.base.generate_main_file(project, config)?;
+        let plan = plan_files(&self.base, &self.project_type, project, config)?;
 
-        info!("Base Python project generated successfully");
-        Ok(())
+        info!("{} Python project planned", self.project_type);
+        Ok(plan)
     }
 }
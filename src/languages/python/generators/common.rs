@@ -1,125 +1,215 @@
 use crate::config::Config;
+use crate::core::plan::Plan;
 use crate::core::project::Project;
 use crate::error::ProjectError;
-use crate::templating::TemplatingEngine;
-use crate::utils::{
-    create_directory, language_context, merge_contexts, project_context, write_file,
+use crate::languages::python::env::{Environment, CONSTRAINTS_FILE};
+use crate::languages::python::pyproject;
+use crate::languages::python::requirements::{
+    pin, read_constraints, requirement_name, specifier_set,
 };
-use log::{debug, error};
-use serde_json::Value;
+use crate::utils::merge_contexts;
+use log::debug;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-const GITIGNORE_TEMPLATE: &str = "gitignore";
-const README_TEMPLATE: &str = "readme";
-const MAIN_TEMPLATE: &str = "main";
-const GITIGNORE_FILE: &str = ".gitignore";
-const README_FILE: &str = "README.md";
-const MAIN_FILE: &str = "main.py";
+pub use crate::languages::common::GeneratorBase;
+
+pub const LANGUAGE: &str = "python";
 const REQUIREMENTS_FILE: &str = "requirements.txt";
+const ENV_TEMPLATE_DIR: &str = "env";
+const PYPROJECT_TEMPLATE_DIR: &str = "pyproject";
+const TEST_GROUP: &str = "test";
+const DEV_GROUP: &str = "dev";
 
-pub struct GeneratorBase {
-    pub engine: TemplatingEngine,
-    template_dir: PathBuf,
+// Layers the packaging files over the project tree: pyproject.toml when the
+// environment needs one, then the backend's own files (`python/env/<backend>`)
+// such as conda's environment.yml.
+pub fn load_env_templates(base: &mut GeneratorBase, config: &Config) -> Result<(), ProjectError> {
+    let env = Environment::from_config(config)?;
+    let pyproject = env
+        .writes_pyproject()
+        .then(|| PathBuf::from(LANGUAGE).join(PYPROJECT_TEMPLATE_DIR));
+    let backend = PathBuf::from(LANGUAGE)
+        .join(ENV_TEMPLATE_DIR)
+        .join(env.backend.name());
+    for dir in pyproject.into_iter().chain(std::iter::once(backend)) {
+        if base.engine.has_template_dir(&dir) {
+            base.engine.load_templates_from_dir(&dir)?;
+        }
+    }
+    Ok(())
 }
 
-impl GeneratorBase {
-    pub fn new(project_type: &str) -> Result<Self, ProjectError> {
-        let template_dir = PathBuf::from("templates/python").join(project_type);
-        let mut engine = TemplatingEngine::new();
-
-        let base_dir = PathBuf::from("templates/python/base");
-        engine.load_templates_from_dir(
-            &base_dir,
-            &[
-                (GITIGNORE_TEMPLATE, "gitignore.template"),
-                (README_TEMPLATE, "readme.template"),
-            ],
-        )?;
+pub fn plan_files(
+    base: &GeneratorBase,
+    project_type: &str,
+    project: &Project,
+    config: &Config,
+) -> Result<Plan, ProjectError> {
+    let env = Environment::from_config(config)?;
+    let groups = DependencyGroups::from_config(config, project_type)?;
+    let constraints = match &env.sources.constraints {
+        Some(path) => Some((
+            std::fs::read(path)?,
+            read_constraints(path).map_err(ProjectError::Config)?,
+        )),
+        None => None,
+    };
+    // Packaged projects are installed with `.[dev]`, so their groups double
+    // as extras
+    let optional = if env.packaged {
+        groups.extras.iter().chain(&groups.groups).collect()
+    } else {
+        groups.extras.iter().collect::<Vec<_>>()
+    };
+    let context = merge_contexts(&[
+        &base.create_context(project, config),
+        &json!({
+            "env": env.backend.name(),
+            "env_setup": env.setup_steps(),
+            "packaged": env.packaged,
+            "pyproject": pyproject::context(project_type, project, config)?,
+            "dependencies": quoted(&groups.runtime),
+            "optional_dependencies": named_lists(optional),
+            "dependency_groups": named_lists(groups.groups.iter().collect()),
+            "index_url": env.sources.index_url,
+            "constraints": constraints.as_ref().map(|(_, specs)| quoted(specs)),
+            // The interpreter's version once one was picked, else the constraint
+            "python_version": config
+                .setting("python_version")
+                .and_then(|v| v.as_str())
+                .unwrap_or(&config.general.version),
+            "requires_python": specifier_set(&config.general.version),
+        }),
+    ]);
 
-        Ok(Self {
-            engine,
-            template_dir,
-        })
+    let mut plan = Plan::new();
+    base.render_files(&mut plan, &context)?;
+    if let Some((contents, _)) = constraints {
+        plan.write_file(CONSTRAINTS_FILE, contents);
     }
-
-    #[allow(dead_code)]
-    pub fn with_main_template(mut self, template_subpath: &str) -> Result<Self, ProjectError> {
-        let main_template_path = self.template_dir.join(template_subpath);
-        self.engine
-            .register_template_file(MAIN_TEMPLATE, main_template_path)?;
-        Ok(self)
+    if env.writes_requirements() {
+        generate_requirements(&mut plan, &groups, env.sources.constraints.is_some());
     }
+    Ok(plan)
+}
 
-    pub fn load_main_template(&mut self, filename: &str) -> Result<(), ProjectError> {
-        self.engine
-            .register_template_file(MAIN_TEMPLATE, self.template_dir.join(filename))
-    }
+// Quoted for pyproject.toml arrays
+fn quoted(specs: &[String]) -> Vec<String> {
+    specs.iter().map(|spec| json!(spec).to_string()).collect()
+}
 
-    pub fn create_project_structure(&self, project: &Project) -> Result<(), ProjectError> {
-        debug!("Creating project directory: {:?}", project.path);
-        create_directory(&project.path)
-    }
+fn named_lists(lists: Vec<(&String, &Vec<String>)>) -> Vec<Value> {
+    lists
+        .into_iter()
+        .map(|(name, specs)| json!({ "name": name, "dependencies": quoted(specs) }))
+        .collect()
+}
 
-    pub fn create_context(&self, project: &Project, config: &Config) -> Value {
-        let project_ctx = project_context(&project.name);
-        let language_ctx = language_context(&config.general.language, &config.general.version);
-        merge_contexts(&[&project_ctx, &language_ctx])
-    }
+// A project type's dependencies split by purpose. The test framework joins
+// the `test` group and `dev` has every group's dependencies, so that a
+// development environment is one install. Versions found by `--pin`
+// (`[settings] pins`) replace the configured specifiers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DependencyGroups {
+    pub runtime: Vec<String>,
+    pub groups: BTreeMap<String, Vec<String>>,
+    pub extras: BTreeMap<String, Vec<String>>,
+}
+
+impl DependencyGroups {
+    pub fn from_config(config: &Config, project_type: &str) -> Result<Self, ProjectError> {
+        let type_config = config.get_project_type_config(project_type)?;
+        let mut groups = type_config.groups.clone();
+        // unittest ships with Python
+        if config.test.framework != "unittest" {
+            add_unique(
+                groups.entry(TEST_GROUP.to_string()).or_default(),
+                [config.test.framework.clone()],
+            );
+        }
+        let others: Vec<String> = groups
+            .iter()
+            .filter(|(name, _)| *name != DEV_GROUP)
+            .flat_map(|(_, specs)| specs.iter().cloned())
+            .collect();
+        if !others.is_empty() {
+            add_unique(groups.entry(DEV_GROUP.to_string()).or_default(), others);
+        }
+        groups.retain(|_, specs| !specs.is_empty());
 
-    pub fn render_and_write(
-        &self,
-        template: &str,
-        filename: &str,
-        context: &Value,
-        project: &Project,
-    ) -> Result<(), ProjectError> {
-        let content = self.engine.render(template, context)?;
-        write_file(project.path.join(filename), content).map_err(|e| {
-            error!("Failed to write file '{}': {}", filename, e);
-            e
-        })
+        let mut dependencies = Self {
+            runtime: type_config.dependencies.clone(),
+            groups,
+            extras: type_config.extras.clone(),
+        };
+        if let Some(pins) = config.setting("pins").and_then(|v| v.as_table()) {
+            dependencies.pin(pins);
+        }
+        Ok(dependencies)
     }
 
-    pub fn generate_base_files(
-        &self,
-        project: &Project,
-        config: &Config,
-    ) -> Result<(), ProjectError> {
-        debug!("Generating base files");
-        let context = self.create_context(project, config);
-        self.render_and_write(GITIGNORE_TEMPLATE, GITIGNORE_FILE, &context, project)?;
-        self.render_and_write(README_TEMPLATE, README_FILE, &context, project)?;
-        Ok(())
+    fn pin(&mut self, pins: &toml::Table) {
+        let lists = std::iter::once(&mut self.runtime)
+            .chain(self.groups.values_mut())
+            .chain(self.extras.values_mut());
+        for spec in lists.flatten() {
+            let version = requirement_name(spec)
+                .and_then(|name| pins.get(&name))
+                .and_then(|v| v.as_str());
+            if let Some(version) = version {
+                *spec = pin(spec, version);
+            }
+        }
     }
 
-    pub fn generate_main_file(
-        &self,
-        project: &Project,
-        config: &Config,
-    ) -> Result<(), ProjectError> {
-        debug!("Generating main.py");
-        let context = self.create_context(project, config);
-        self.render_and_write(MAIN_TEMPLATE, MAIN_FILE, &context, project)
+    // Everything any environment of the project could install
+    pub fn all(&self) -> Vec<String> {
+        let mut all = self.runtime.clone();
+        for specs in self.groups.values().chain(self.extras.values()) {
+            add_unique(&mut all, specs.iter().cloned());
+        }
+        all
     }
+}
 
-    pub fn generate_requirements(
-        &self,
-        project: &Project,
-        dependencies: &[String],
-        test_framework: Option<&str>,
-    ) -> Result<(), ProjectError> {
-        debug!("Generating requirements.txt");
-        let mut content = dependencies.join("\n");
-        if let Some(framework) = test_framework {
-            if !content.is_empty() {
-                content.push('\n');
-            }
-            content.push_str(framework);
+fn add_unique(list: &mut Vec<String>, specs: impl IntoIterator<Item = String>) {
+    for spec in specs {
+        if !list.contains(&spec) {
+            list.push(spec);
         }
-        content.push('\n');
-        write_file(project.path.join(REQUIREMENTS_FILE), content).map_err(|e| {
-            error!("Failed to write requirements file: {}", e);
-            e
-        })
     }
 }
+
+// requirements.txt has the runtime dependencies only, and the constraints
+// file when there is one; every group and extra gets a
+// `requirements-<name>.txt` on top of it. requirements-dev.txt is always
+// written since environments install it.
+pub fn generate_requirements(plan: &mut Plan, groups: &DependencyGroups, constrained: bool) {
+    debug!("Generating requirements files");
+    let constraints = constrained.then(|| format!("-c {}", CONSTRAINTS_FILE));
+    let runtime: Vec<String> = constraints
+        .into_iter()
+        .chain(groups.runtime.iter().cloned())
+        .collect();
+    plan.write_file(REQUIREMENTS_FILE, lines(&runtime));
+    let include = format!("-r {}", REQUIREMENTS_FILE);
+    let mut files: BTreeMap<&str, Vec<String>> = groups
+        .groups
+        .iter()
+        .chain(&groups.extras)
+        .map(|(name, specs)| (name.as_str(), specs.clone()))
+        .collect();
+    files.entry(DEV_GROUP).or_default();
+    for (name, specs) in files {
+        let content = std::iter::once(include.clone())
+            .chain(specs)
+            .collect::<Vec<_>>();
+        plan.write_file(format!("requirements-{}.txt", name), lines(&content));
+    }
+}
+
+fn lines(specs: &[String]) -> String {
+    specs.iter().map(|spec| format!("{}\n", spec)).collect()
+}
@@ -1,55 +1,44 @@
-use super::common::GeneratorBase;
+use super::common::{load_env_templates, plan_files, GeneratorBase, LANGUAGE};
 use crate::config::Config;
 use crate::core::generator::ProjectGenerator;
+use crate::core::plan::Plan;
 use crate::core::project::Project;
 use crate::error::ProjectError;
-use log::{error, info};
+use crate::templating::TemplateManifest;
+use log::info;
+use serde_json::Value;
 
 pub struct DataScienceGenerator {
     base: GeneratorBase,
 }
 
-impl Default for DataScienceGenerator {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 impl DataScienceGenerator {
-    pub fn new() -> Self {
-        let mut base = match GeneratorBase::new("data_science") {
-            Ok(b) => b,
-            Err(e) => {
-                error!("Failed to create generator base: {}", e);
-                GeneratorBase::new("data_science")
-                    .unwrap_or_else(|_| panic!("Cannot create generator"))
-            }
-        };
-
-        if let Err(e) = base.load_main_template("main.py.template") {
-            error!("Failed to load data science main template: {}", e);
-        }
-
-        DataScienceGenerator { base }
+    pub fn new(config: &Config) -> Result<Self, ProjectError> {
+        let mut base = GeneratorBase::new(LANGUAGE, "data_science", config)?;
+
+        base.load_type_templates(None)?;
+
+        load_env_templates(&mut base, config)?;
+
+        Ok(DataScienceGenerator { base })
     }
 }
 
 impl ProjectGenerator for DataScienceGenerator {
-    fn generate(&self, project: &Project, config: &Config) -> Result<(), ProjectError> {
-        info!("Generating Python Data Science project: {}", project.name);
-
-        self.base.create_project_structure(project)?;
-        self.base.generate_base_files(project, config)?;
-        self.base.generate_main_file(project, config)?;
-
-        let ds_config = config.get_project_type_config("data_science");
-        self.base.generate_requirements(
-            project,
-            &ds_config.dependencies,
-            Some(&config.test.framework),
-        )?;
-
-        info!("Python Data Science project generated successfully");
-        Ok(())
+    fn manifest(&self) -> &TemplateManifest {
+        self.base.manifest()
+    }
+
+    fn create_context(&self, project: &Project, config: &Config) -> Value {
+        self.base.create_context(project, config)
+    }
+
+    fn plan(&self, project: &Project, config: &Config) -> Result<Plan, ProjectError> {
+        info!("Planning Python Data Science project: {}", project.name);
+
+        let plan = plan_files(&self.base, "data_science", project, config)?;
+
+        info!("Python Data Science project planned");
+        Ok(plan)
     }
 }
@@ -1,59 +1,62 @@
-use super::common::GeneratorBase;
+use super::common::{load_env_templates, plan_files, GeneratorBase, LANGUAGE};
 use crate::config::Config;
 use crate::core::generator::ProjectGenerator;
+use crate::core::plan::Plan;
 use crate::core::project::Project;
 use crate::error::ProjectError;
-use log::{error, info};
+use crate::templating::TemplateManifest;
+use log::info;
+use serde_json::Value;
 use std::path::PathBuf;
 
 pub struct WebGenerator {
     base: GeneratorBase,
 }
 
-impl Default for WebGenerator {
-    fn default() -> Self {
-        Self::new()
-    }
-}
+const DEFAULT_FRAMEWORK: &str = "fastapi";
 
 impl WebGenerator {
-    pub fn new() -> Self {
-        let mut base = match GeneratorBase::new("web") {
-            Ok(b) => b,
-            Err(e) => {
-                error!("Failed to create generator base: {}", e);
-                GeneratorBase::new("web").unwrap_or_else(|_| panic!("Cannot create generator"))
-            }
-        };
-
-        let web_template_path = PathBuf::from("templates/python/web/fastapi/main.py.template");
-        if let Err(e) = base
-            .engine
-            .register_template_file("main", web_template_path)
-        {
-            error!("Failed to load web main template: {}", e);
+    // The framework picks the `python/web/<framework>` template variant
+    pub fn new(config: &Config) -> Result<Self, ProjectError> {
+        let framework = config
+            .get_project_type_config("web")?
+            .framework
+            .as_deref()
+            .unwrap_or(DEFAULT_FRAMEWORK);
+
+        let mut base = GeneratorBase::new(LANGUAGE, "web", config)?;
+
+        let variant = PathBuf::from(LANGUAGE).join("web").join(framework);
+        if !base.engine.has_template_dir(&variant) {
+            return Err(ProjectError::Config(format!(
+                "Unsupported web framework '{}': no '{}' templates",
+                framework,
+                variant.display()
+            )));
         }
+        base.load_type_templates(Some(framework))?;
+
+        load_env_templates(&mut base, config)?;
 
-        WebGenerator { base }
+        Ok(WebGenerator { base })
     }
 }
 
 impl ProjectGenerator for WebGenerator {
-    fn generate(&self, project: &Project, config: &Config) -> Result<(), ProjectError> {
-        info!("Generating Web project: {}", project.name);
-
-        self.base.create_project_structure(project)?;
-        self.base.generate_base_files(project, config)?;
-        self.base.generate_main_file(project, config)?;
-
-        let web_config = config.get_project_type_config("web");
-        self.base.generate_requirements(
-            project,
-            &web_config.dependencies,
-            Some(&config.test.framework),
-        )?;
-
-        info!("Web project generated successfully");
-        Ok(())
+    fn manifest(&self) -> &TemplateManifest {
+        self.base.manifest()
+    }
+
+    fn create_context(&self, project: &Project, config: &Config) -> Value {
+        self.base.create_context(project, config)
+    }
+
+    fn plan(&self, project: &Project, config: &Config) -> Result<Plan, ProjectError> {
+        info!("Planning Web project: {}", project.name);
+
+        let plan = plan_files(&self.base, "web", project, config)?;
+
+        info!("Web project planned");
+        Ok(plan)
     }
 }
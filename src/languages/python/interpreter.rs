@@ -0,0 +1,112 @@
+use super::requirements::satisfies;
+use crate::error::ProjectError;
+use log::debug;
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+
+// `python`, `python3` and `python3.X`, but not `python3-config`
+static NAME_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^python(?:3(?:\.[0-9]+)?)?$").unwrap());
+
+const SYSTEM_DIRS: &[&str] = &["/usr/local/bin", "/usr/bin", "/opt/homebrew/bin"];
+const VERSION_SCRIPT: &str = "import sys; print('.'.join(map(str, sys.version_info[:3])))";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interpreter {
+    pub path: PathBuf,
+    pub version: String,
+}
+
+// Every interpreter that answers a version query, in search order
+pub fn discover() -> impl Iterator<Item = Interpreter> {
+    let mut seen = HashSet::new();
+    candidates()
+        .into_iter()
+        .filter(move |path| seen.insert(path.canonicalize().unwrap_or_else(|_| path.clone())))
+        .filter_map(|path| {
+            let version = query_version(&path)?;
+            debug!("Found Python {} at {}", version, path.display());
+            Some(Interpreter { path, version })
+        })
+}
+
+// The first interpreter satisfying `constraint`, so that the one on PATH is
+// preferred; `interpreter` replaces the search when configured
+pub fn find(constraint: &str, interpreter: Option<&Path>) -> Result<Interpreter, ProjectError> {
+    let mut found = Vec::new();
+    let interpreters: Box<dyn Iterator<Item = Interpreter>> = match interpreter {
+        Some(path) => Box::new(
+            query_version(path)
+                .map(|version| Interpreter {
+                    path: path.to_path_buf(),
+                    version,
+                })
+                .into_iter(),
+        ),
+        None => Box::new(discover()),
+    };
+    for interpreter in interpreters {
+        if satisfies(&interpreter.version, constraint) {
+            return Ok(interpreter);
+        }
+        found.push(format!(
+            "{} ({})",
+            interpreter.path.display(),
+            interpreter.version
+        ));
+    }
+    Err(ProjectError::NoInterpreter {
+        constraint: constraint.to_string(),
+        found,
+    })
+}
+
+// PATH (which includes pyenv shims when pyenv is set up), installed pyenv
+// versions, then the usual system locations
+fn candidates() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect())
+        .unwrap_or_default();
+    let pyenv_root = std::env::var_os("PYENV_ROOT")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".pyenv")));
+    if let Some(Ok(versions)) = pyenv_root.map(|root| root.join("versions").read_dir()) {
+        let mut installed: Vec<PathBuf> = versions
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path().join("bin"))
+            .collect();
+        installed.sort();
+        dirs.extend(installed);
+    }
+    dirs.extend(SYSTEM_DIRS.iter().map(PathBuf::from));
+
+    let mut candidates = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = dir.read_dir() else {
+            continue;
+        };
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|name| NAME_RE.is_match(name))
+            .collect();
+        names.sort();
+        candidates.extend(names.into_iter().map(|name| dir.join(name)));
+    }
+    candidates
+}
+
+fn query_version(path: &Path) -> Option<String> {
+    let output = Command::new(path)
+        .args(["-c", VERSION_SCRIPT])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!version.is_empty()).then_some(version)
+}
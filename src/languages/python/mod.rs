@@ -1,58 +1,238 @@
+pub mod env;
 pub mod generators;
+pub mod interpreter;
+pub mod pin;
+pub mod pyproject;
+pub mod requirements;
 
-use crate::config::Config;
+use crate::config::{unknown_setting, Config};
+use crate::core::executor::Executor;
 use crate::core::generator::ProjectGenerator;
 use crate::core::language::Language;
+use crate::core::plan::Plan;
 use crate::core::project::Project;
 use crate::error::ProjectError;
-use log::{debug, error, info};
+use env::{EnvBackend, Environment, PackageSources, ENV_BACKENDS};
+use generators::common::DependencyGroups;
+use log::{info, warn};
+use std::path::{Path, PathBuf};
+
+// `[settings]` keys; `python_version` and `pins` are set while generating
+const SETTINGS: &[&str] = &[
+    "env",
+    "find_links",
+    "index_url",
+    "interpreter",
+    "pyproject",
+    "build_backend",
+    "constraints",
+    "pin",
+];
 
 pub struct Python;
 
 impl Language for Python {
-    fn get_generator(&self, _config: &Config, project_type: &str) -> Box<dyn ProjectGenerator> {
-        let generator = match project_type {
-            "web" => Box::new(generators::web::WebGenerator::new()) as Box<dyn ProjectGenerator>,
-            "cli" => Box::new(generators::cli::CliGenerator::new()) as Box<dyn ProjectGenerator>,
-            "data_science" => Box::new(generators::data_science::DataScienceGenerator::new())
-                as Box<dyn ProjectGenerator>,
-            _ => Box::new(generators::base::BaseGenerator::new()) as Box<dyn ProjectGenerator>,
-        };
-        debug!(
-            "Selected generator type: {}",
-            std::any::type_name_of_val(&generator)
+    fn name(&self) -> &'static str {
+        "python"
+    }
+
+    fn check_version(&self, version: &str) -> Result<(), String> {
+        requirements::check_version_constraint(version)
+    }
+
+    fn check_dependency(&self, spec: &str) -> Result<(), String> {
+        requirements::check_requirement(spec)
+    }
+
+    fn test_frameworks(&self) -> &'static [&'static str] {
+        &["pytest", "unittest"]
+    }
+
+    fn framework_types(&self) -> &'static [&'static str] {
+        &["web"]
+    }
+
+    fn check_setting(&self, key: &str, value: &toml::Value) -> Result<(), String> {
+        match key {
+            "env" => match value.as_str() {
+                Some(env) if ENV_BACKENDS.contains(&env) => Ok(()),
+                _ => Err(format!(
+                    "unknown environment {} (expected one of: {})",
+                    value,
+                    ENV_BACKENDS.join(", ")
+                )),
+            },
+            "find_links" => match value.as_str() {
+                Some(dir) if Path::new(dir).is_dir() => Ok(()),
+                Some(dir) => Err(format!("wheel directory '{}' does not exist", dir)),
+                None => Err(format!("expected a directory, got {}", value)),
+            },
+            "interpreter" => match value.as_str() {
+                Some(_) => Ok(()),
+                None => Err(format!("expected a path, got {}", value)),
+            },
+            "constraints" => match value.as_str() {
+                Some(file) => requirements::read_constraints(Path::new(file)).map(|_| ()),
+                None => Err(format!("expected a file, got {}", value)),
+            },
+            "pyproject" | "pin" => match value.as_bool() {
+                Some(_) => Ok(()),
+                None => Err(format!("expected true or false, got {}", value)),
+            },
+            "build_backend" => match value.as_str() {
+                Some(backend) if pyproject::build_backend_names().contains(&backend) => Ok(()),
+                _ => Err(format!(
+                    "unknown build backend {} (expected one of: {})",
+                    value,
+                    pyproject::build_backend_names().join(", ")
+                )),
+            },
+            "index_url" => match value.as_str() {
+                Some(url) if url.contains("://") => Ok(()),
+                _ => Err(format!("expected a URL, got {}", value)),
+            },
+            _ => Err(unknown_setting(key, SETTINGS)),
+        }
+    }
+
+    // The interpreter is looked up for backends that run one: conda brings
+    // its own and `none` runs nothing.
+    fn resolve_toolchain(&self, config: &mut Config) -> Result<(), ProjectError> {
+        if !EnvBackend::from_config(config)?.uses_interpreter() {
+            return Ok(());
+        }
+        let found = find_interpreter(config)?;
+        config.settings.insert(
+            "interpreter".to_string(),
+            toml::Value::String(found.path.display().to_string()),
+        );
+        config.settings.insert(
+            "python_version".to_string(),
+            toml::Value::String(found.version),
         );
-        generator
+        Ok(())
+    }
+
+    // `[settings] pin` resolves exact versions with pip, from the configured
+    // sources and within the constraints; generators apply them from
+    // `[settings] pins`
+    fn resolve_dependencies(
+        &self,
+        config: &mut Config,
+        project_type: &str,
+        executor: &Executor,
+    ) -> Result<(), ProjectError> {
+        if !config
+            .setting("pin")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+        {
+            return Ok(());
+        }
+        if EnvBackend::from_config(config)? == EnvBackend::None {
+            warn!("Not pinning dependencies: the `none` environment runs no commands");
+            return Ok(());
+        }
+        let requirements = DependencyGroups::from_config(config, project_type)?.all();
+        if requirements.is_empty() {
+            return Ok(());
+        }
+        let python = config
+            .setting("interpreter")
+            .and_then(|v| v.as_str())
+            .unwrap_or("python");
+        let versions = pin::resolve(
+            executor,
+            python,
+            &requirements,
+            &PackageSources::from_config(config)?,
+        )?;
+        let mut pins = toml::Table::new();
+        for name in requirements
+            .iter()
+            .filter_map(|spec| requirements::requirement_name(spec))
+        {
+            if let Some(version) = versions.get(&name) {
+                info!("Pinned {} to {}", name, version);
+                pins.insert(name, toml::Value::String(version.clone()));
+            }
+        }
+        config
+            .settings
+            .insert("pins".to_string(), toml::Value::Table(pins));
+        Ok(())
+    }
+
+    fn dry_run_note(&self, config: &Config) -> Option<String> {
+        let pin = config
+            .setting("pin")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        Some(
+            if pin {
+                "The Python interpreter isn't looked up and dependency versions aren't pinned."
+            } else {
+                "The Python interpreter isn't looked up."
+            }
+            .to_string(),
+        )
     }
 
-    fn setup_environment(&self, project: &Project, _config: &Config) -> Result<(), ProjectError> {
+    // Project types without a dedicated generator are planned from the config
+    // and their templates alone.
+    fn get_generator(
+        &self,
+        config: &Config,
+        project_type: &str,
+    ) -> Result<Box<dyn ProjectGenerator>, ProjectError> {
+        config.get_project_type_config(project_type)?;
+        let generator: Box<dyn ProjectGenerator> = match project_type {
+            "web" => Box::new(generators::web::WebGenerator::new(config)?),
+            "cli" => Box::new(generators::cli::CliGenerator::new(config)?),
+            "data_science" => {
+                Box::new(generators::data_science::DataScienceGenerator::new(config)?)
+            }
+            other => Box::new(generators::base::BaseGenerator::for_type(config, other)?),
+        };
+        Ok(generator)
+    }
+
+    fn setup_environment(&self, project: &Project, config: &Config) -> Result<Plan, ProjectError> {
+        let env = Environment::from_config(config)?;
         info!(
-            "Setting up Python environment for project: {}",
+            "Planning {} environment for project: {}",
+            env.backend.name(),
             project.name
         );
 
-        let venv_path = project.path.join("venv");
-        debug!("Creating virtual environment at: {:?}", venv_path);
-        std::process::Command::new("python")
-            .args(["-m", "venv", venv_path.to_str().unwrap()])
-            .output()
-            .map_err(|e| {
-                error!("Failed to create virtual environment: {}", e);
-                ProjectError::Generator(format!("Failed to create virtual environment: {}", e))
-            })?;
-
-        let requirements_path = project.path.join("requirements.txt");
-        if requirements_path.exists() {
-            std::process::Command::new(venv_path.join("bin").join("pip"))
-                .args(["install", "-r", requirements_path.to_str().unwrap()])
-                .output()
-                .map_err(|e| {
-                    error!("Failed to install dependencies: {}", e);
-                    ProjectError::Generator(format!("Failed to install dependencies: {}", e))
-                })?;
-        }
-
-        info!("Python environment set up successfully");
-        Ok(())
+        let mut plan = Plan::new();
+        for command in env.commands()? {
+            plan.run_command(command);
+        }
+        Ok(plan)
     }
+
+    fn next_steps(&self, project: &Project, config: &Config) -> Vec<String> {
+        let Ok(env) = Environment::from_config(config) else {
+            return vec![format!("cd {}", project.path.display())];
+        };
+        let mut steps = vec![format!("cd {}", project.path.display())];
+        if env.backend == EnvBackend::None {
+            steps.extend(env.setup_steps());
+        }
+        steps.push(env.backend.run_command("main.py"));
+        steps
+    }
+}
+
+// A Python matching `general.version`, or the configured `interpreter` once
+// it is checked against it
+pub fn find_interpreter(config: &Config) -> Result<interpreter::Interpreter, ProjectError> {
+    let configured = config
+        .setting("interpreter")
+        .and_then(|v| v.as_str())
+        .map(PathBuf::from);
+    let found = interpreter::find(&config.general.version, configured.as_deref())?;
+    info!("Using Python {} at {}", found.version, found.path.display());
+    Ok(found)
 }
@@ -0,0 +1,59 @@
+use super::env::PackageSources;
+use super::requirements::normalize_name;
+use crate::core::executor::Executor;
+use crate::core::plan::CommandSpec;
+use crate::error::ProjectError;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+// The versions pip would install for `requirements` from `sources` today, by
+// normalized name. `pip install --dry-run --report` resolves them together,
+// so the versions are compatible with each other and the constraints file.
+pub fn resolve(
+    executor: &Executor,
+    python: &str,
+    requirements: &[String],
+    sources: &PackageSources,
+) -> Result<BTreeMap<String, String>, ProjectError> {
+    let command = resolve_command(python, requirements, sources);
+    let output = executor.capture(&command, Path::new("."))?;
+    let report: Value = serde_json::from_str(&output).map_err(|e| {
+        ProjectError::Generator(format!("Failed to read pip's installation report: {}", e))
+    })?;
+    let versions = report["install"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            let metadata = &item["metadata"];
+            Some((
+                normalize_name(metadata["name"].as_str()?),
+                metadata["version"].as_str()?.to_string(),
+            ))
+        })
+        .collect();
+    Ok(versions)
+}
+
+// `--ignore-installed` so that packages already in the interpreter's
+// environment get resolved too
+fn resolve_command(python: &str, requirements: &[String], sources: &PackageSources) -> CommandSpec {
+    CommandSpec::new(
+        python,
+        &[
+            "-m",
+            "pip",
+            "install",
+            "--dry-run",
+            "--ignore-installed",
+            "--quiet",
+            "--report",
+            "-",
+        ],
+        "resolve dependency versions",
+    )
+    .with_args(sources.install_args())
+    .with_args(sources.constraint_args())
+    .with_args(requirements.iter().cloned())
+}
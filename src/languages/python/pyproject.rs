@@ -0,0 +1,80 @@
+use crate::config::Config;
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use crate::languages::python::requirements::specifier_set;
+use serde_json::{json, Value};
+
+// Name, `[build-system] requires` and `build-backend`
+pub const BUILD_BACKENDS: &[(&str, &str, &str)] = &[
+    ("hatchling", "hatchling", "hatchling.build"),
+    ("setuptools", "setuptools>=77", "setuptools.build_meta"),
+    ("flit", "flit_core>=3.12", "flit_core.buildapi"),
+    ("pdm-backend", "pdm-backend", "pdm.backend"),
+    ("poetry-core", "poetry-core>=2.0", "poetry.core.masonry.api"),
+];
+// Metadata and dependencies only, nothing to build
+pub const NO_BUILD_BACKEND: &str = "none";
+const DEFAULT_BUILD_BACKEND: &str = "hatchling";
+
+pub fn build_backend_names() -> Vec<&'static str> {
+    BUILD_BACKENDS
+        .iter()
+        .map(|(name, _, _)| *name)
+        .chain(std::iter::once(NO_BUILD_BACKEND))
+        .collect()
+}
+
+// Strings come quoted, ready to be used as TOML values
+pub fn context(
+    project_type: &str,
+    project: &Project,
+    config: &Config,
+) -> Result<Value, ProjectError> {
+    let quote = |s: &str| json!(s).to_string();
+    let variable = |key: &str| {
+        project
+            .variables
+            .get(key)
+            .and_then(Value::as_str)
+            .filter(|v| !v.is_empty())
+    };
+    let backend = config
+        .setting("build_backend")
+        .and_then(|v| v.as_str())
+        .unwrap_or(DEFAULT_BUILD_BACKEND);
+    let build_system = match BUILD_BACKENDS.iter().find(|(name, _, _)| *name == backend) {
+        Some((_, requires, build_backend)) => {
+            json!({ "requires": quote(requires), "build_backend": quote(build_backend) })
+        }
+        None if backend == NO_BUILD_BACKEND => Value::Null,
+        None => {
+            return Err(ProjectError::Config(format!(
+                "Unknown build backend '{}' (expected one of: {})",
+                backend,
+                build_backend_names().join(", ")
+            )))
+        }
+    };
+    // PEP 639 wants SPDX expressions
+    let license = variable("license").map(|license| match license {
+        "Proprietary" => quote("LicenseRef-Proprietary"),
+        spdx => quote(spdx),
+    });
+    let entry_point = config
+        .get_project_type_config(project_type)?
+        .entry_point
+        .as_deref()
+        .map(quote);
+
+    Ok(json!({
+        "name": quote(&project.name),
+        "description": quote(variable("description").unwrap_or_default()),
+        "author": variable("author").map(quote),
+        "license": license,
+        "requires_python": quote(&specifier_set(&config.general.version)),
+        "script": quote(&project.name),
+        "entry_point": entry_point,
+        "backend": backend,
+        "build_system": build_system,
+    }))
+}
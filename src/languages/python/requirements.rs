@@ -0,0 +1,232 @@
+use regex::Regex;
+use std::path::Path;
+use std::sync::LazyLock;
+
+// PEP 440 as accepted by pip: an optional `v`, epoch, release, pre-, post-
+// and dev-release, and a local version label
+const VERSION: &str = r"v?(?:[0-9]+!)?[0-9]+(?:\.[0-9]+)*(?:[-_.]?(?:alpha|a|beta|b|preview|pre|c|rc)[-_.]?[0-9]*)?(?:-[0-9]+|[-_.]?(?:post|rev|r)[-_.]?[0-9]*)?(?:[-_.]?dev[-_.]?[0-9]*)?(?:\+[a-z0-9]+(?:[-_.][a-z0-9]+)*)?";
+
+static VERSION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(&format!("(?i)^{}$", VERSION)).unwrap());
+
+// `==` and `!=` also accept a `.*` prefix match; `===` compares strings
+static CLAUSE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        r"(?i)^(?:(?:==|!=)\s*{v}(?:\.\*)?|(?:~=|<=|>=|<|>)\s*{v}|===\s*\S+)$",
+        v = VERSION
+    ))
+    .unwrap()
+});
+
+// PEP 508: a name, optional extras, then version specifiers or `@ url`
+static REQUIREMENT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^([A-Za-z0-9](?:[A-Za-z0-9._-]*[A-Za-z0-9])?)\s*(?:\[\s*[A-Za-z0-9._-]+(?:\s*,\s*[A-Za-z0-9._-]+)*\s*\])?\s*(.*)$",
+    )
+    .unwrap()
+});
+
+static SEPARATORS_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[-_.]+").unwrap());
+
+pub fn is_valid_version(version: &str) -> bool {
+    VERSION_RE.is_match(version.trim())
+}
+
+// Checks the syntax of a requirement such as `requests[socks]>=2.31,<3`;
+// environment markers after `;` are not interpreted.
+pub fn check_requirement(spec: &str) -> Result<(), String> {
+    let (requirement, marker) = match spec.split_once(';') {
+        Some((requirement, marker)) => (requirement, Some(marker)),
+        None => (spec, None),
+    };
+    if marker.is_some_and(|marker| marker.trim().is_empty()) {
+        return Err(format!("invalid requirement '{}': empty marker", spec));
+    }
+    let captures = REQUIREMENT_RE
+        .captures(requirement.trim())
+        .ok_or_else(|| format!("invalid requirement '{}': expected a package name", spec))?;
+    let rest = captures.get(2).map_or("", |m| m.as_str()).trim();
+
+    if let Some(url) = rest.strip_prefix('@') {
+        return if url.trim().is_empty() {
+            Err(format!(
+                "invalid requirement '{}': missing URL after '@'",
+                spec
+            ))
+        } else {
+            Ok(())
+        };
+    }
+    let specifiers = rest
+        .strip_prefix('(')
+        .and_then(|rest| rest.strip_suffix(')'))
+        .unwrap_or(rest);
+    if specifiers.trim().is_empty() {
+        return Ok(());
+    }
+    for clause in specifiers.split(',') {
+        if !CLAUSE_RE.is_match(clause.trim()) {
+            return Err(format!(
+                "invalid requirement '{}': bad version specifier '{}'",
+                spec,
+                clause.trim()
+            ));
+        }
+    }
+    Ok(())
+}
+
+// The PEP 503 normalized name of a requirement, e.g. `foo-bar` for
+// `Foo_Bar>=1`; None for invalid requirements
+pub fn requirement_name(spec: &str) -> Option<String> {
+    let requirement = spec.split(';').next().unwrap_or_default();
+    let captures = REQUIREMENT_RE.captures(requirement.trim())?;
+    Some(normalize_name(&captures[1]))
+}
+
+pub fn normalize_name(name: &str) -> String {
+    SEPARATORS_RE
+        .replace_all(&name.to_lowercase(), "-")
+        .into_owned()
+}
+
+// Replaces the version specifiers of `spec` with `==version`, keeping extras
+// and markers; direct URL references are left as they are
+pub fn pin(spec: &str, version: &str) -> String {
+    let (requirement, marker) = match spec.split_once(';') {
+        Some((requirement, marker)) => (requirement, Some(marker.trim())),
+        None => (spec, None),
+    };
+    let Some(captures) = REQUIREMENT_RE.captures(requirement.trim()) else {
+        return spec.to_string();
+    };
+    let rest = captures.get(2).unwrap();
+    if rest.as_str().trim().starts_with('@') {
+        return spec.to_string();
+    }
+    let name_and_extras: String = requirement.trim()[..rest.start()]
+        .split_whitespace()
+        .collect();
+    match marker {
+        Some(marker) => format!("{}=={}; {}", name_and_extras, version, marker),
+        None => format!("{}=={}", name_and_extras, version),
+    }
+}
+
+// The requirements of a pip constraints file, one per line with `#`
+// comments; pip options such as `-r` are not supported
+pub fn read_constraints(path: &Path) -> Result<Vec<String>, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("can't read '{}': {}", path.display(), e))?;
+    let mut constraints = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        let line = match line.find('#') {
+            Some(start) => &line[..start],
+            None => line,
+        }
+        .trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('-') {
+            return Err(format!(
+                "{}:{}: options like '{}' are not supported",
+                path.display(),
+                number + 1,
+                line
+            ));
+        }
+        check_requirement(line).map_err(|e| format!("{}:{}: {}", path.display(), number + 1, e))?;
+        constraints.push(line.to_string());
+    }
+    Ok(constraints)
+}
+
+// `general.version` is either a version, meaning at least that version, or a
+// specifier set such as `>=3.10,<3.13`
+pub fn check_version_constraint(constraint: &str) -> Result<(), String> {
+    if is_valid_version(constraint)
+        || constraint
+            .split(',')
+            .all(|clause| CLAUSE_RE.is_match(clause.trim()))
+    {
+        Ok(())
+    } else {
+        Err(format!(
+            "'{}' is not a valid PEP 440 version or version specifier",
+            constraint
+        ))
+    }
+}
+
+// The constraint as a specifier set, for `requires-python`
+pub fn specifier_set(constraint: &str) -> String {
+    let constraint = constraint.trim();
+    if is_valid_version(constraint) {
+        format!(">={}", constraint)
+    } else {
+        constraint
+            .split(',')
+            .map(|clause| clause.split_whitespace().collect::<String>())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+// Compares release numbers only, which is enough for interpreter versions
+pub fn satisfies(version: &str, constraint: &str) -> bool {
+    let version = release(version);
+    specifier_set(constraint).split(',').all(|clause| {
+        let Some(op_end) = clause.find(|c: char| c.is_ascii_digit() || c == 'v') else {
+            return false;
+        };
+        let (op, target) = clause.split_at(op_end);
+        if let Some(prefix) = target.strip_suffix(".*") {
+            let prefix = release(prefix);
+            let matches = version.len() >= prefix.len() && version[..prefix.len()] == prefix;
+            return if op == "!=" { !matches } else { matches };
+        }
+        let target = release(target);
+        let ordering = compare(&version, &target);
+        match op {
+            "==" | "===" => ordering.is_eq(),
+            "!=" => ordering.is_ne(),
+            ">=" => ordering.is_ge(),
+            "<=" => ordering.is_le(),
+            ">" => ordering.is_gt(),
+            "<" => ordering.is_lt(),
+            // `~=3.10` is `>=3.10,==3.*`
+            "~=" => {
+                let prefix = &target[..target.len().saturating_sub(1).max(1)];
+                ordering.is_ge()
+                    && version.len() >= prefix.len()
+                    && version[..prefix.len()] == *prefix
+            }
+            _ => false,
+        }
+    })
+}
+
+fn compare(a: &[u64], b: &[u64]) -> std::cmp::Ordering {
+    let len = a.len().max(b.len());
+    let padded = |v: &[u64]| -> Vec<u64> {
+        v.iter()
+            .copied()
+            .chain(std::iter::repeat(0))
+            .take(len)
+            .collect()
+    };
+    padded(a).cmp(&padded(b))
+}
+
+fn release(version: &str) -> Vec<u64> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .map_while(|part| {
+            let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+            digits.parse().ok()
+        })
+        .collect()
+}
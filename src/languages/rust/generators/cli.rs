@@ -0,0 +1,47 @@
+use super::LANGUAGE;
+use crate::config::Config;
+use crate::core::generator::ProjectGenerator;
+use crate::core::plan::Plan;
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use crate::languages::common::GeneratorBase;
+use crate::templating::TemplateManifest;
+use log::info;
+use serde_json::Value;
+use std::path::PathBuf;
+
+pub struct CliGenerator {
+    base: GeneratorBase,
+}
+
+impl CliGenerator {
+    pub fn new(config: &Config) -> Result<Self, ProjectError> {
+        let mut base = GeneratorBase::new(LANGUAGE, "cli", config)?;
+
+        // The CLI is a binary crate with the clap skeleton layered on top
+        base.engine
+            .load_templates_from_dir(PathBuf::from(LANGUAGE).join("bin"))?;
+        base.load_type_templates(None)?;
+
+        Ok(CliGenerator { base })
+    }
+}
+
+impl ProjectGenerator for CliGenerator {
+    fn manifest(&self) -> &TemplateManifest {
+        self.base.manifest()
+    }
+
+    fn create_context(&self, project: &Project, config: &Config) -> Value {
+        self.base.create_context(project, config)
+    }
+
+    fn plan(&self, project: &Project, config: &Config) -> Result<Plan, ProjectError> {
+        info!("Planning Rust CLI project: {}", project.name);
+
+        let plan = super::plan_files(&self.base, "cli", project, config)?;
+
+        info!("Rust CLI project planned");
+        Ok(plan)
+    }
+}
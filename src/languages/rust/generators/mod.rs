@@ -0,0 +1,63 @@
+pub mod bin;
+pub mod cli;
+pub mod lib;
+pub mod workspace;
+
+use crate::config::Config;
+use crate::core::plan::Plan;
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use crate::languages::common::GeneratorBase;
+use crate::utils::merge_contexts;
+use serde_json::json;
+
+pub const LANGUAGE: &str = "rust";
+
+pub fn plan_files(
+    base: &GeneratorBase,
+    project_type: &str,
+    project: &Project,
+    config: &Config,
+) -> Result<Plan, ProjectError> {
+    let dependencies =
+        dependency_lines(&config.get_project_type_config(project_type)?.dependencies)?;
+    let context = merge_contexts(&[
+        &base.create_context(project, config),
+        &json!({ "dependencies": dependencies }),
+    ]);
+
+    let mut plan = Plan::new();
+    base.render_files(&mut plan, &context)?;
+    Ok(plan)
+}
+
+// `clap@4.5+derive` becomes `clap = { version = "4.5", features = ["derive"] }`
+pub fn dependency_lines(dependencies: &[String]) -> Result<Vec<String>, ProjectError> {
+    dependencies
+        .iter()
+        .map(|spec| {
+            let mut parts = spec.split('+');
+            let requirement = parts.next().unwrap_or_default();
+            let (name, version) = requirement
+                .split_once('@')
+                .filter(|(name, version)| !name.is_empty() && !version.is_empty())
+                .ok_or_else(|| {
+                    ProjectError::Config(format!(
+                        "Invalid Rust dependency '{}': expected name@version",
+                        spec
+                    ))
+                })?;
+            let features: Vec<String> = parts.map(|f| format!("\"{}\"", f)).collect();
+            Ok(if features.is_empty() {
+                format!("{} = \"{}\"", name, version)
+            } else {
+                format!(
+                    "{} = {{ version = \"{}\", features = [{}] }}",
+                    name,
+                    version,
+                    features.join(", ")
+                )
+            })
+        })
+        .collect()
+}
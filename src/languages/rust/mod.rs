@@ -0,0 +1,73 @@
+pub mod generators;
+
+use crate::config::{unknown_project_type, Config};
+use crate::core::generator::ProjectGenerator;
+use crate::core::language::Language;
+use crate::core::plan::Plan;
+use crate::core::project::Project;
+use crate::error::ProjectError;
+use log::info;
+
+const PROJECT_TYPES: &[&str] = &["bin", "cli", "lib", "workspace"];
+const EDITIONS: &[&str] = &["2018", "2021", "2024"];
+
+pub struct Rust;
+
+impl Language for Rust {
+    fn name(&self) -> &'static str {
+        "rust"
+    }
+
+    // The version is the edition
+    fn check_version(&self, version: &str) -> Result<(), String> {
+        if EDITIONS.contains(&version) {
+            Ok(())
+        } else {
+            Err(format!(
+                "'{}' is not a Rust edition (expected one of: {})",
+                version,
+                EDITIONS.join(", ")
+            ))
+        }
+    }
+
+    fn check_dependency(&self, spec: &str) -> Result<(), String> {
+        generators::dependency_lines(&[spec.to_string()])
+            .map(|_| ())
+            .map_err(|e| e.detail())
+    }
+
+    fn test_frameworks(&self) -> &'static [&'static str] {
+        &["libtest"]
+    }
+
+    fn get_generator(
+        &self,
+        config: &Config,
+        project_type: &str,
+    ) -> Result<Box<dyn ProjectGenerator>, ProjectError> {
+        config.get_project_type_config(project_type)?;
+        let generator: Box<dyn ProjectGenerator> = match project_type {
+            "cli" => Box::new(generators::cli::CliGenerator::new(config)?),
+            "lib" => Box::new(generators::lib::LibGenerator::new(config)?),
+            "workspace" => Box::new(generators::workspace::WorkspaceGenerator::new(config)?),
+            "bin" => Box::new(generators::bin::BinGenerator::new(config)?),
+            other => return Err(unknown_project_type(other, PROJECT_TYPES)),
+        };
+        Ok(generator)
+    }
+
+    // Cargo resolves dependencies on the first build, so nothing runs here
+    fn setup_environment(&self, project: &Project, _config: &Config) -> Result<Plan, ProjectError> {
+        info!("Planning Rust environment for project: {}", project.name);
+        Ok(Plan::new())
+    }
+
+    fn next_steps(&self, project: &Project, _config: &Config) -> Vec<String> {
+        vec![
+            format!("cd {}", project.path.display()),
+            "cargo build".to_string(),
+            "cargo test".to_string(),
+        ]
+    }
+}
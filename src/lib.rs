@@ -6,7 +6,6 @@ pub mod languages;
 pub mod templating;
 pub mod utils;
 
-
 pub use config::Config;
 pub use core::generator::ProjectGenerator;
 pub use error::ProjectError;
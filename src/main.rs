@@ -1,12 +1,5 @@
-mod cli;
-mod config;
-mod core;
-mod error;
-mod languages;
-mod templating;
-mod utils;
-
 use log::{debug, error, info};
+use poq::cli;
 
 #[tokio::main]
 async fn main() {
@@ -1,11 +1,31 @@
+use super::loader::TemplateLoader;
+use super::manifest::{TemplateManifest, MANIFEST_FILE};
 use crate::error::ProjectError;
 use handlebars::Handlebars;
 use log::{debug, error};
 use serde_json::Value;
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+const TEMPLATE_EXTENSION: &str = "template";
+
+enum TreeEntry {
+    Template(String),
+    Source(String),
+    Binary(Vec<u8>),
+}
+
+pub struct RenderedFile {
+    pub path: PathBuf,
+    pub contents: Vec<u8>,
+}
 
 pub struct TemplatingEngine {
     handlebars: Handlebars<'static>,
+    loader: TemplateLoader,
+    tree: BTreeMap<PathBuf, TreeEntry>,
+    manifest: TemplateManifest,
+    main_template: Option<PathBuf>,
 }
 
 impl Default for TemplatingEngine {
@@ -16,8 +36,22 @@ impl Default for TemplatingEngine {
 
 impl TemplatingEngine {
     pub fn new() -> Self {
+        Self::with_loader(TemplateLoader::new())
+    }
+
+    pub fn with_search_path(dirs: &[PathBuf]) -> Self {
+        Self::with_loader(TemplateLoader::with_dirs(dirs))
+    }
+
+    pub fn with_loader(loader: TemplateLoader) -> Self {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_escape_fn(handlebars::no_escape);
         TemplatingEngine {
-            handlebars: Handlebars::new(),
+            handlebars,
+            loader,
+            tree: BTreeMap::new(),
+            manifest: TemplateManifest::default(),
+            main_template: None,
         }
     }
 
@@ -29,7 +63,6 @@ impl TemplatingEngine {
         })
     }
 
-    #[allow(dead_code)]
     pub fn render_string(&self, template: &str, data: &Value) -> Result<String, ProjectError> {
         debug!("Rendering string template");
         self.handlebars
@@ -46,40 +79,166 @@ impl TemplatingEngine {
             .map_err(|e| ProjectError::Template(e.into()))
     }
 
-    pub fn register_template_file<P: AsRef<Path>>(
+    pub fn load_template<P: AsRef<Path>>(
         &mut self,
         name: &str,
         path: P,
     ) -> Result<(), ProjectError> {
-        debug!(
-            "Registering template '{}' from file: {:?}",
-            name,
-            path.as_ref()
-        );
-        let content = std::fs::read_to_string(&path).map_err(|e| {
-            error!(
-                "Failed to read template file '{}': {}",
-                path.as_ref().display(),
-                e
-            );
-            ProjectError::Config(format!(
-                "Failed to read template '{}': {}",
-                path.as_ref().display(),
-                e
-            ))
-        })?;
+        debug!("Loading template '{}' from: {:?}", name, path.as_ref());
+        let content = self.loader.read_to_string(path)?;
         self.register_template(name, &content)
     }
 
-    pub fn load_templates_from_dir<P: AsRef<Path>>(
-        &mut self,
-        dir: P,
-        mappings: &[(&str, &str)],
-    ) -> Result<(), ProjectError> {
+    // Later directories override files with the same (unrendered) output path,
+    // so a project type can replace files from the base template.
+    pub fn load_templates_from_dir<P: AsRef<Path>>(&mut self, dir: P) -> Result<(), ProjectError> {
         debug!("Loading templates from directory: {:?}", dir.as_ref());
-        for (name, filename) in mappings {
-            self.register_template_file(name, dir.as_ref().join(filename))?;
+        for file in self.loader.read_dir(&dir)? {
+            if file.path == Path::new(MANIFEST_FILE) {
+                let origin = dir.as_ref().join(MANIFEST_FILE).display().to_string();
+                let source = String::from_utf8_lossy(&file.contents);
+                self.manifest
+                    .merge(TemplateManifest::parse(&source, &origin)?);
+                continue;
+            }
+            let output_path = strip_template_extension(&file.path);
+            let entry = match String::from_utf8(file.contents) {
+                Ok(text) if !text.contains('\0') => {
+                    let name = format!("{}/{}", dir.as_ref().display(), file.path.display());
+                    self.register_template(&name, &text)?;
+                    TreeEntry::Template(name)
+                }
+                Ok(text) => TreeEntry::Binary(text.into_bytes()),
+                Err(e) => TreeEntry::Binary(e.into_bytes()),
+            };
+            self.tree.insert(output_path, entry);
         }
         Ok(())
     }
+
+    // Read when the tree is rendered, so it may point at a template in a
+    // directory that isn't loaded (e.g. another web framework's `main.py`).
+    pub fn set_main_template<P: AsRef<Path>>(&mut self, template: P) {
+        self.main_template = Some(template.as_ref().to_path_buf());
+    }
+
+    pub fn has_template_dir<P: AsRef<Path>>(&self, dir: P) -> bool {
+        self.loader.has_dir(dir)
+    }
+
+    pub fn manifest(&self) -> &TemplateManifest {
+        &self.manifest
+    }
+
+    pub fn render_tree(&self, data: &Value) -> Result<Vec<RenderedFile>, ProjectError> {
+        let main = self.main_entry()?;
+        let main_path = main.as_ref().map(|(path, _)| path);
+        let entries = self
+            .tree
+            .iter()
+            .filter(|(path, _)| Some(*path) != main_path)
+            .chain(main.as_ref().map(|(path, entry)| (path, entry)));
+        let mut files = Vec::with_capacity(self.tree.len() + 1);
+        for (path, entry) in entries {
+            let Some(path) = self.render_path(path, data)? else {
+                debug!(
+                    "Skipping '{}': a path component rendered empty",
+                    path.display()
+                );
+                continue;
+            };
+            let contents = match entry {
+                TreeEntry::Template(name) => self.render(name, data)?.into_bytes(),
+                TreeEntry::Source(source) => self.render_string(source, data)?.into_bytes(),
+                TreeEntry::Binary(bytes) => bytes.clone(),
+            };
+            files.push(RenderedFile { path, contents });
+        }
+        Ok(files)
+    }
+
+    // The main template replaces the tree file with the same name, or is
+    // added at the root if there is none. A main template that is part of a
+    // loaded tree is already in place.
+    fn main_entry(&self) -> Result<Option<(PathBuf, TreeEntry)>, ProjectError> {
+        let Some(template) = &self.main_template else {
+            return Ok(None);
+        };
+        let name = template.display().to_string();
+        let loaded = self
+            .tree
+            .values()
+            .any(|entry| matches!(entry, TreeEntry::Template(loaded) if *loaded == name));
+        if loaded {
+            return Ok(None);
+        }
+
+        let source = self.loader.read_to_string(template)?;
+        let file_name = template
+            .file_name()
+            .map(|name| strip_template_extension(Path::new(name)))
+            .ok_or_else(|| {
+                ProjectError::Config(format!("Invalid main file template '{}'", name))
+            })?;
+        let targets: Vec<&PathBuf> = self
+            .tree
+            .keys()
+            .filter(|path| path.file_name() == Some(file_name.as_os_str()))
+            .collect();
+        let path = match targets.as_slice() {
+            [] => file_name,
+            [path] => path.to_path_buf(),
+            _ => {
+                return Err(ProjectError::Config(format!(
+                    "Main file template '{}' matches several files: {}",
+                    name,
+                    targets
+                        .iter()
+                        .map(|p| p.display().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )))
+            }
+        };
+        debug!(
+            "Using main file template '{}' for '{}'",
+            name,
+            path.display()
+        );
+        Ok(Some((path, TreeEntry::Source(source))))
+    }
+
+    // A component rendering to an empty string (e.g. an unset `{{docs_file}}`)
+    // drops the file, which lets templates include files conditionally. A
+    // component may render to several `/`-separated directories, such as a
+    // Java package path.
+    fn render_path(&self, path: &Path, data: &Value) -> Result<Option<PathBuf>, ProjectError> {
+        let mut rendered = PathBuf::new();
+        for component in path.components() {
+            let name = self.render_string(&component.as_os_str().to_string_lossy(), data)?;
+            if name.is_empty() {
+                return Ok(None);
+            }
+            for part in name.split('/') {
+                match Path::new(part).components().next() {
+                    Some(Component::Normal(_)) if !part.contains('\\') => rendered.push(part),
+                    _ => {
+                        return Err(ProjectError::Generator(format!(
+                            "Template path '{}' renders to invalid file name '{}'",
+                            path.display(),
+                            name
+                        )))
+                    }
+                }
+            }
+        }
+        Ok(Some(rendered))
+    }
+}
+
+fn strip_template_extension(path: &Path) -> PathBuf {
+    match path.extension() {
+        Some(ext) if ext == TEMPLATE_EXTENSION => path.with_extension(""),
+        _ => path.to_path_buf(),
+    }
 }
@@ -0,0 +1,176 @@
+use crate::error::ProjectError;
+use crate::utils::user_templates_dir;
+use include_dir::{include_dir, Dir};
+use log::{debug, error};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const TEMPLATES_DIR_ENV: &str = "POQ_TEMPLATES_DIR";
+
+static BUILTIN_TEMPLATES: Dir<'static> = include_dir!("$CARGO_MANIFEST_DIR/templates");
+
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub contents: Vec<u8>,
+}
+
+// Search order: explicit dirs (`--templates-dir`, `[templates]`), then
+// `POQ_TEMPLATES_DIR`, then `~/.config/poq/templates`, then the embedded tree.
+#[derive(Debug, Clone)]
+pub struct TemplateLoader {
+    search_path: Vec<PathBuf>,
+}
+
+impl Default for TemplateLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TemplateLoader {
+    pub fn new() -> Self {
+        Self::with_dirs(&[])
+    }
+
+    pub fn with_dirs(dirs: &[PathBuf]) -> Self {
+        let mut search_path = dirs.to_vec();
+        if let Some(value) = env::var_os(TEMPLATES_DIR_ENV) {
+            search_path.extend(env::split_paths(&value).filter(|p| !p.as_os_str().is_empty()));
+        }
+        search_path.extend(user_templates_dir());
+        debug!("Template search path: {:?}", search_path);
+        Self { search_path }
+    }
+
+    pub fn isolated(dirs: &[PathBuf]) -> Self {
+        Self {
+            search_path: dirs.to_vec(),
+        }
+    }
+
+    pub fn search_path(&self) -> &[PathBuf] {
+        &self.search_path
+    }
+
+    pub fn read_to_string<P: AsRef<Path>>(&self, path: P) -> Result<String, ProjectError> {
+        let path = path.as_ref();
+        for dir in &self.search_path {
+            let candidate = dir.join(path);
+            if candidate.is_file() {
+                debug!("Loading template from: {:?}", candidate);
+                return fs::read_to_string(&candidate).map_err(|e| {
+                    error!("Failed to read template '{}': {}", candidate.display(), e);
+                    ProjectError::Config(format!(
+                        "Failed to read template '{}': {}",
+                        candidate.display(),
+                        e
+                    ))
+                });
+            }
+        }
+
+        debug!("Loading built-in template: {:?}", path);
+        BUILTIN_TEMPLATES
+            .get_file(path)
+            .and_then(|file| file.contents_utf8())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                error!("Template '{}' not found", path.display());
+                ProjectError::Config(format!(
+                    "Template '{}' not found in {:?} or the built-in templates",
+                    path.display(),
+                    self.search_path
+                ))
+            })
+    }
+
+    pub fn has_dir<P: AsRef<Path>>(&self, dir: P) -> bool {
+        let dir = dir.as_ref();
+        self.search_path.iter().any(|root| root.join(dir).is_dir())
+            || BUILTIN_TEMPLATES.get_dir(dir).is_some()
+    }
+
+    // The first search directory containing `dir` supplies the whole tree;
+    // trees are not merged file by file across search directories.
+    pub fn read_dir<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<SourceFile>, ProjectError> {
+        let dir = dir.as_ref();
+        for root in &self.search_path {
+            let candidate = root.join(dir);
+            if candidate.is_dir() {
+                debug!("Loading template directory from: {:?}", candidate);
+                let mut files = Vec::new();
+                walk_fs(&candidate, Path::new(""), &mut files)?;
+                return Ok(files);
+            }
+        }
+
+        debug!("Loading built-in template directory: {:?}", dir);
+        let builtin = BUILTIN_TEMPLATES.get_dir(dir).ok_or_else(|| {
+            error!("Template directory '{}' not found", dir.display());
+            ProjectError::Config(format!(
+                "Template directory '{}' not found in {:?} or the built-in templates",
+                dir.display(),
+                self.search_path
+            ))
+        })?;
+        let mut files = Vec::new();
+        walk_builtin(builtin, dir, &mut files);
+        Ok(files)
+    }
+
+    pub fn has_file<P: AsRef<Path>>(&self, path: P) -> bool {
+        let path = path.as_ref();
+        self.search_path
+            .iter()
+            .any(|root| root.join(path).is_file())
+            || BUILTIN_TEMPLATES.get_file(path).is_some()
+    }
+}
+
+fn walk_fs(root: &Path, relative: &Path, files: &mut Vec<SourceFile>) -> Result<(), ProjectError> {
+    let dir = root.join(relative);
+    let mut entries = fs::read_dir(&dir)
+        .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| {
+            error!(
+                "Failed to read template directory '{}': {}",
+                dir.display(),
+                e
+            );
+            ProjectError::Io(e)
+        })?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = relative.join(entry.file_name());
+        if entry.path().is_dir() {
+            walk_fs(root, &path, files)?;
+        } else {
+            let contents = fs::read(entry.path()).map_err(|e| {
+                error!(
+                    "Failed to read template '{}': {}",
+                    entry.path().display(),
+                    e
+                );
+                ProjectError::Io(e)
+            })?;
+            files.push(SourceFile { path, contents });
+        }
+    }
+    Ok(())
+}
+
+fn walk_builtin(dir: &Dir<'static>, prefix: &Path, files: &mut Vec<SourceFile>) {
+    for file in dir.files() {
+        if let Ok(path) = file.path().strip_prefix(prefix) {
+            files.push(SourceFile {
+                path: path.to_path_buf(),
+                contents: file.contents().to_vec(),
+            });
+        }
+    }
+    for subdir in dir.dirs() {
+        walk_builtin(subdir, prefix, files);
+    }
+}
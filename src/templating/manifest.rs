@@ -0,0 +1,185 @@
+use crate::error::ProjectError;
+use handlebars::Handlebars;
+use log::debug;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+pub const MANIFEST_FILE: &str = "poq.toml";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableType {
+    #[default]
+    String,
+    Bool,
+    Choice,
+    Int,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VariableSpec {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub kind: VariableType,
+    pub prompt: Option<String>,
+    // String defaults are Handlebars expressions over the answers given so far
+    pub default: Option<toml::Value>,
+    pub validation: Option<String>,
+    pub help: Option<String>,
+    #[serde(default)]
+    pub choices: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateManifest {
+    #[serde(default)]
+    pub variables: Vec<VariableSpec>,
+}
+
+impl TemplateManifest {
+    pub fn parse(source: &str, origin: &str) -> Result<Self, ProjectError> {
+        let manifest: TemplateManifest = toml::from_str(source).map_err(|e| {
+            ProjectError::Config(format!("Failed to parse manifest '{}': {}", origin, e))
+        })?;
+        for spec in &manifest.variables {
+            spec.check().map_err(|e| {
+                ProjectError::Config(format!(
+                    "Invalid variable '{}' in manifest '{}': {}",
+                    spec.name, origin, e
+                ))
+            })?;
+        }
+        Ok(manifest)
+    }
+
+    // Variables from a later manifest replace earlier ones with the same name
+    // and keep their position; new variables are appended.
+    pub fn merge(&mut self, other: TemplateManifest) {
+        for spec in other.variables {
+            match self.variables.iter_mut().find(|v| v.name == spec.name) {
+                Some(existing) => *existing = spec,
+                None => self.variables.push(spec),
+            }
+        }
+    }
+
+    // Asks `answer` for each variable in declaration order, passing the
+    // rendered default, and validates whatever it returns.
+    pub fn resolve<F>(
+        &self,
+        base: &Value,
+        mut answer: F,
+    ) -> Result<Map<String, Value>, ProjectError>
+    where
+        F: FnMut(&VariableSpec, Option<Value>) -> Result<Value, ProjectError>,
+    {
+        let mut context = base.as_object().cloned().unwrap_or_default();
+        let mut answers = Map::new();
+        for spec in &self.variables {
+            let default = spec.default_value(&Value::Object(context.clone()))?;
+            let value = answer(spec, default)?;
+            spec.validate(&value).map_err(|e| {
+                ProjectError::Config(format!("Invalid value for '{}': {}", spec.name, e))
+            })?;
+            debug!("Variable '{}' = {}", spec.name, value);
+            context.insert(spec.name.clone(), value.clone());
+            answers.insert(spec.name.clone(), value);
+        }
+        Ok(answers)
+    }
+}
+
+impl VariableSpec {
+    pub fn prompt_text(&self) -> String {
+        let prompt = self.prompt.as_deref().unwrap_or(&self.name);
+        match &self.help {
+            Some(help) => format!("{} ({})", prompt, help),
+            None => prompt.to_string(),
+        }
+    }
+
+    pub fn default_value(&self, context: &Value) -> Result<Option<Value>, ProjectError> {
+        let Some(default) = &self.default else {
+            return Ok(None);
+        };
+        let raw = match default {
+            toml::Value::String(template) => {
+                let mut handlebars = Handlebars::new();
+                handlebars.register_escape_fn(handlebars::no_escape);
+                handlebars
+                    .render_template(template, context)
+                    .map_err(ProjectError::Template)?
+            }
+            other => other.to_string(),
+        };
+        self.parse_value(&raw).map(Some).map_err(|e| {
+            ProjectError::Config(format!("Invalid default for '{}': {}", self.name, e))
+        })
+    }
+
+    pub fn parse_value(&self, raw: &str) -> Result<Value, String> {
+        let raw = raw.trim();
+        let value = match self.kind {
+            VariableType::String | VariableType::Choice => Value::String(raw.to_string()),
+            VariableType::Bool => match raw.to_lowercase().as_str() {
+                "true" | "yes" | "y" | "1" => Value::Bool(true),
+                "false" | "no" | "n" | "0" => Value::Bool(false),
+                _ => return Err(format!("'{}' is not a boolean", raw)),
+            },
+            VariableType::Int => raw
+                .parse::<i64>()
+                .map(Value::from)
+                .map_err(|_| format!("'{}' is not an integer", raw))?,
+        };
+        self.validate(&value)?;
+        Ok(value)
+    }
+
+    pub fn validate(&self, value: &Value) -> Result<(), String> {
+        match (self.kind, value) {
+            (VariableType::String, Value::String(s)) => self.check_pattern(s),
+            (VariableType::Choice, Value::String(s)) => {
+                if self.choices.contains(s) {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "'{}' is not one of: {}",
+                        s,
+                        self.choices.join(", ")
+                    ))
+                }
+            }
+            (VariableType::Bool, Value::Bool(_)) => Ok(()),
+            (VariableType::Int, Value::Number(n)) if n.is_i64() => {
+                self.check_pattern(&n.to_string())
+            }
+            (kind, value) => Err(format!("expected {:?}, got {}", kind, value)),
+        }
+    }
+
+    fn check_pattern(&self, value: &str) -> Result<(), String> {
+        let Some(pattern) = &self.validation else {
+            return Ok(());
+        };
+        // The whole value has to match, whether or not the pattern is anchored
+        let regex = Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| e.to_string())?;
+        if regex.is_match(value) {
+            Ok(())
+        } else {
+            Err(format!("'{}' does not match '{}'", value, pattern))
+        }
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if let Some(pattern) = &self.validation {
+            Regex::new(pattern).map_err(|e| format!("invalid validation regex: {}", e))?;
+        }
+        if self.kind == VariableType::Choice && self.choices.is_empty() {
+            return Err("choice variables need a non-empty `choices` list".to_string());
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,7 @@
 mod engine;
+mod loader;
+pub mod manifest;
 
-pub use engine::TemplatingEngine;
+pub use engine::{RenderedFile, TemplatingEngine};
+pub use loader::{SourceFile, TemplateLoader, TEMPLATES_DIR_ENV};
+pub use manifest::{TemplateManifest, VariableSpec, VariableType};
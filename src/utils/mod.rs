@@ -1,5 +1,7 @@
 pub mod file_operations;
+pub mod paths;
 pub mod template_helpers;
 
 pub use file_operations::{create_directory, write_file};
+pub use paths::{find_executable, user_config_dir, user_templates_dir};
 pub use template_helpers::{language_context, merge_contexts, project_context};
@@ -5,6 +5,9 @@ pub fn project_context(project_name: &str) -> Value {
         "project_name": project_name,
         "project_name_lowercase": project_name.to_lowercase(),
         "project_name_uppercase": project_name.to_uppercase(),
+        "package_name": package_name(project_name),
+        // The identifier Cargo derives from a package name
+        "crate_name": project_name.replace('-', "_"),
     })
 }
 
@@ -25,3 +28,11 @@ pub fn merge_contexts(contexts: &[&Value]) -> Value {
             .collect()
     })
 }
+
+pub fn package_name(project_name: &str) -> String {
+    project_name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
@@ -1,7 +1,7 @@
 use poq::config::Config;
 use poq::core::project::Project;
 use poq::templating::TemplatingEngine;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[test]
 fn test_config_loading() {
@@ -26,13 +26,13 @@ fn test_config_new() {
 fn test_config_get_project_type() {
     let config = Config::load("config/python/default.toml").unwrap();
 
-    let web_config = config.get_project_type_config("web");
+    let web_config = config.get_project_type_config("web").unwrap();
     assert!(web_config.dependencies.contains(&"fastapi".to_string()));
 
-    let cli_config = config.get_project_type_config("cli");
+    let cli_config = config.get_project_type_config("cli").unwrap();
     assert!(cli_config.dependencies.contains(&"argparse".to_string()));
 
-    let ds_config = config.get_project_type_config("data_science");
+    let ds_config = config.get_project_type_config("data_science").unwrap();
     assert!(ds_config.dependencies.contains(&"pandas".to_string()));
 }
 
@@ -122,3 +122,1965 @@ fn test_config_validation() {
     let result = config.validate();
     assert!(result.is_ok());
 }
+
+#[test]
+fn test_builtin_templates_are_embedded() {
+    use poq::templating::TemplateLoader;
+
+    let loader = TemplateLoader::isolated(&[]);
+    let main = loader.read_to_string("python/base/main.py.template");
+    assert!(main.is_ok(), "Built-in template should be embedded");
+    assert!(main.unwrap().contains("{{project_name}}"));
+
+    assert!(loader
+        .read_to_string("python/base/missing.template")
+        .is_err());
+}
+
+#[test]
+fn test_template_search_path_overrides_builtin() {
+    use poq::templating::TemplateLoader;
+
+    let dir = tempfile::tempdir().unwrap();
+    let override_dir = dir.path().join("python/base");
+    std::fs::create_dir_all(&override_dir).unwrap();
+    std::fs::write(override_dir.join("main.py.template"), "print('custom')").unwrap();
+
+    let loader = TemplateLoader::isolated(&[dir.path().to_path_buf()]);
+    assert_eq!(
+        loader
+            .read_to_string("python/base/main.py.template")
+            .unwrap(),
+        "print('custom')"
+    );
+    // Files missing from the override directory still come from the binary
+    assert!(loader.read_to_string("python/cli/main.py.template").is_ok());
+}
+
+#[test]
+fn test_render_template_tree() {
+    use poq::templating::TemplateLoader;
+
+    let dir = tempfile::tempdir().unwrap();
+    let template = dir.path().join("custom");
+    std::fs::create_dir_all(template.join("src/{{package_name}}")).unwrap();
+    std::fs::write(
+        template.join("src/{{package_name}}/__init__.py.template"),
+        "NAME = '{{project_name}}'\n",
+    )
+    .unwrap();
+    std::fs::write(template.join("logo.png"), [0x89, b'P', b'N', b'G', 0, 0xff]).unwrap();
+    std::fs::write(template.join("{{docs_file}}"), "docs").unwrap();
+
+    let mut engine =
+        TemplatingEngine::with_loader(TemplateLoader::isolated(&[dir.path().to_path_buf()]));
+    engine.load_templates_from_dir("custom").unwrap();
+    let files = engine
+        .render_tree(&serde_json::json!({"project_name": "My-App", "package_name": "my_app"}))
+        .unwrap();
+
+    let paths: Vec<_> = files.iter().map(|f| f.path.clone()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("logo.png"),
+            PathBuf::from("src/my_app/__init__.py")
+        ]
+    );
+    assert_eq!(files[0].contents, vec![0x89, b'P', b'N', b'G', 0, 0xff]);
+    assert_eq!(files[1].contents, b"NAME = 'My-App'\n");
+}
+
+#[test]
+fn test_builtin_python_tree_layers_project_type() {
+    use poq::templating::TemplateLoader;
+
+    let mut engine = TemplatingEngine::with_loader(TemplateLoader::isolated(&[]));
+    engine.load_templates_from_dir("python/base").unwrap();
+    engine
+        .load_templates_from_dir("python/web/fastapi")
+        .unwrap();
+    let files = engine
+        .render_tree(&serde_json::json!({"project_name": "api"}))
+        .unwrap();
+
+    let main = files
+        .iter()
+        .find(|f| f.path == Path::new("main.py"))
+        .unwrap();
+    assert!(String::from_utf8_lossy(&main.contents).contains("FastAPI"));
+    assert!(files.iter().any(|f| f.path == Path::new(".gitignore")));
+    assert!(files.iter().any(|f| f.path == Path::new("README.md")));
+}
+
+#[test]
+fn test_manifest_resolves_defaults_from_earlier_answers() {
+    use poq::templating::TemplateManifest;
+
+    let manifest = TemplateManifest::parse(
+        r#"
+        [[variables]]
+        name = "module"
+        default = "{{project_name}}_core"
+        validation = "^[a-z_]+$"
+
+        [[variables]]
+        name = "docs"
+        type = "bool"
+        default = true
+
+        [[variables]]
+        name = "port"
+        type = "int"
+        default = "8000"
+
+        [[variables]]
+        name = "title"
+        default = "{{module}} service"
+        "#,
+        "poq.toml",
+    )
+    .unwrap();
+
+    let answers = manifest
+        .resolve(&serde_json::json!({"project_name": "app"}), |_, default| {
+            Ok(default.unwrap())
+        })
+        .unwrap();
+    assert_eq!(answers["module"], "app_core");
+    assert_eq!(answers["docs"], true);
+    assert_eq!(answers["port"], 8000);
+    assert_eq!(answers["title"], "app_core service");
+}
+
+#[test]
+fn test_manifest_validation() {
+    use poq::templating::TemplateManifest;
+
+    let manifest = TemplateManifest::parse(
+        r#"
+        [[variables]]
+        name = "module"
+        validation = "^[a-z_]+$"
+
+        [[variables]]
+        name = "license"
+        type = "choice"
+        choices = ["MIT", "Apache-2.0"]
+        "#,
+        "poq.toml",
+    )
+    .unwrap();
+    let module = &manifest.variables[0];
+    assert!(module.parse_value("my_module").is_ok());
+    assert!(module.parse_value("My-Module").is_err());
+    assert!(manifest.variables[1].parse_value("GPL").is_err());
+
+    // Unanchored patterns match the whole value too
+    let unanchored = TemplateManifest::parse(
+        "[[variables]]\nname = \"slug\"\nvalidation = \"[a-z]+\"\n",
+        "poq.toml",
+    )
+    .unwrap();
+    assert!(unanchored.variables[0].parse_value("foo").is_ok());
+    assert!(unanchored.variables[0].parse_value("Foo-bar!").is_err());
+
+    let missing_choices = TemplateManifest::parse(
+        "[[variables]]\nname = \"x\"\ntype = \"choice\"\n",
+        "poq.toml",
+    );
+    assert!(missing_choices.is_err());
+}
+
+#[test]
+fn test_builtin_manifest_is_loaded() {
+    use poq::templating::TemplateLoader;
+
+    let mut engine = TemplatingEngine::with_loader(TemplateLoader::isolated(&[]));
+    engine.load_templates_from_dir("python/base").unwrap();
+    let names: Vec<_> = engine
+        .manifest()
+        .variables
+        .iter()
+        .map(|v| v.name.as_str())
+        .collect();
+    assert!(names.contains(&"description"));
+
+    let files = engine
+        .render_tree(&serde_json::json!({"project_name": "app"}))
+        .unwrap();
+    assert!(!files.iter().any(|f| f.path == Path::new("poq.toml")));
+}
+
+#[test]
+fn test_non_interactive_answers() {
+    use poq::cli::answers::AnswerSource;
+    use poq::templating::TemplateManifest;
+
+    let manifest = TemplateManifest::parse(
+        r#"
+        [[variables]]
+        name = "module"
+
+        [[variables]]
+        name = "port"
+        type = "int"
+
+        [[variables]]
+        name = "docs"
+        type = "bool"
+        default = false
+        "#,
+        "poq.toml",
+    )
+    .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let answers_file = dir.path().join("answers.json");
+    std::fs::write(&answers_file, r#"{"port": 9000, "extra": "kept"}"#).unwrap();
+
+    let none = AnswerSource::new(false, false);
+    assert_eq!(none.missing(&manifest), vec!["module", "port"]);
+
+    let answers = AnswerSource::new(false, false)
+        .with_vars(&["module=core", "port=8080"])
+        .unwrap()
+        .with_answers_file(&answers_file)
+        .unwrap();
+    assert!(answers.missing(&manifest).is_empty());
+
+    let variables = answers
+        .resolve(&manifest, &serde_json::json!({}), &["name"])
+        .unwrap();
+    assert_eq!(variables["module"], "core");
+    assert_eq!(variables["port"], 8080);
+    assert_eq!(variables["docs"], false);
+    assert_eq!(variables["extra"], "kept");
+
+    let invalid = AnswerSource::new(false, false)
+        .with_vars(&["module=core", "port=eighty"])
+        .unwrap();
+    assert!(invalid
+        .resolve(&manifest, &serde_json::json!({}), &[])
+        .is_err());
+    assert!(AnswerSource::new(false, false)
+        .with_vars(&["no-equals-sign"])
+        .is_err());
+}
+
+#[test]
+fn test_no_input_reports_missing_arguments() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_poq"))
+        .args(["--no-input", "python"])
+        .env("RUST_LOG", "error")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Missing required input: project_type, name"),
+        "unexpected stderr: {}",
+        stderr
+    );
+}
+
+#[test]
+fn test_plan_and_execute() {
+    use poq::core::executor::Executor;
+    use poq::core::{Operation, Plan};
+
+    let mut plan = Plan::new();
+    plan.write_file("src/pkg/__init__.py", "");
+    plan.write_file("README.md", "first");
+    plan.write_file("README.md", "second");
+    assert_eq!(
+        plan.operations()[..2],
+        [
+            Operation::CreateDirectory(PathBuf::from("src")),
+            Operation::CreateDirectory(PathBuf::from("src/pkg")),
+        ]
+    );
+    assert_eq!(plan.files().count(), 2);
+
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("project");
+    let description = plan.describe(&root);
+    assert!(description.contains("create file"));
+    assert!(!root.exists(), "describe must not touch disk");
+
+    Executor::new().execute(&plan, &root).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(root.join("README.md")).unwrap(),
+        "second"
+    );
+    assert!(root.join("src/pkg/__init__.py").is_file());
+    assert!(plan.describe(&root).contains("overwrite"));
+}
+
+#[test]
+fn test_python_generator_plans_without_writing() {
+    use poq::languages::get_language;
+
+    let config = Config::new().unwrap();
+    let python = get_language("python").unwrap();
+    let generator = python.get_generator(&config, "cli").unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let project = Project::new("tool", dir.path().join("tool"));
+
+    let plan = generator.plan(&project, &config).unwrap();
+    let files: Vec<_> = plan.files().map(|(path, _)| path.to_path_buf()).collect();
+    assert!(files.contains(&PathBuf::from("main.py")));
+    assert!(files.contains(&PathBuf::from("requirements.txt")));
+    assert!(!project.path.exists());
+
+    let env_plan = python.setup_environment(&project, &config).unwrap();
+    assert!(env_plan
+        .commands()
+        .any(|c| c.command_line() == "python -m venv venv"));
+}
+
+#[test]
+fn test_broken_templates_are_errors() {
+    use poq::config::ConfigLayers;
+    use poq::languages::get_language;
+
+    let dir = tempfile::tempdir().unwrap();
+    for base in ["python/base", "rust/base"] {
+        std::fs::create_dir_all(dir.path().join(base)).unwrap();
+        std::fs::write(dir.path().join(base).join("poq.toml"), "variables = [").unwrap();
+    }
+    for (language, project_type) in [("python", "cli"), ("python", "base"), ("rust", "lib")] {
+        let config = ConfigLayers::new()
+            .with_overrides(&[format!(
+                "templates.dirs=[{:?}]",
+                dir.path().display().to_string()
+            )])
+            .unwrap()
+            .resolve(language)
+            .unwrap()
+            .config;
+        let err = get_language(language)
+            .unwrap()
+            .get_generator(&config, project_type)
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string().contains("Failed to parse manifest"),
+            "{}",
+            err
+        );
+    }
+}
+
+fn failing_plan() -> poq::core::Plan {
+    use poq::core::{CommandSpec, Plan};
+
+    let mut plan = Plan::new();
+    plan.write_file("main.py", "print('new')");
+    plan.write_file("pkg/__init__.py", "");
+    plan.run_command(CommandSpec::new(
+        "poq-test-program-that-does-not-exist",
+        &[],
+        "run a missing program",
+    ));
+    plan
+}
+
+#[test]
+fn test_failed_generation_rolls_back() {
+    use poq::core::executor::Executor;
+
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("project");
+    assert!(Executor::new().execute(&failing_plan(), &root).is_err());
+    assert!(!root.exists());
+    assert_eq!(
+        std::fs::read_dir(dir.path()).unwrap().count(),
+        0,
+        "staging directories should be cleaned up"
+    );
+
+    let kept = Executor::new()
+        .keep_on_failure(true)
+        .execute(&failing_plan(), &root);
+    assert!(kept.is_err());
+    assert!(root.join("main.py").is_file());
+}
+
+#[test]
+fn test_failed_generation_restores_existing_directory() {
+    use poq::core::executor::Executor;
+
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("project");
+    std::fs::create_dir(&root).unwrap();
+    std::fs::write(root.join("main.py"), "print('mine')").unwrap();
+
+    assert!(Executor::new().execute(&failing_plan(), &root).is_err());
+    assert_eq!(
+        std::fs::read_to_string(root.join("main.py")).unwrap(),
+        "print('mine')"
+    );
+    assert!(!root.join("pkg").exists());
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    // Whatever a failing setup command created goes too
+    #[cfg(unix)]
+    {
+        use poq::core::{CommandSpec, Plan};
+
+        let mut plan = Plan::new();
+        plan.write_file("main.py", "print('new')");
+        plan.run_command(CommandSpec::new(
+            "sh",
+            &[
+                "-c",
+                "mkdir venv && touch venv/python install.log && exit 1",
+            ],
+            "create an environment",
+        ));
+        assert!(Executor::new().execute(&plan, &root).is_err());
+        let mut entries: Vec<_> = std::fs::read_dir(&root)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        entries.sort();
+        assert_eq!(entries, ["main.py"]);
+        assert_eq!(
+            std::fs::read_to_string(root.join("main.py")).unwrap(),
+            "print('mine')"
+        );
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_command_exit_status_is_checked() {
+    use poq::core::executor::Executor;
+    use poq::core::{CommandSpec, Plan};
+    use poq::ProjectError;
+    use std::time::{Duration, Instant};
+
+    let plan_running = |script: &str, optional: bool| {
+        let mut plan = Plan::new();
+        plan.write_file("main.py", "");
+        let command = CommandSpec::new("sh", &["-c", script], "run a script");
+        plan.run_command(if optional {
+            command.optional()
+        } else {
+            command
+        });
+        plan
+    };
+    let dir = tempfile::tempdir().unwrap();
+
+    let failing = "for i in $(seq 1 30); do echo line $i >&2; done; exit 3";
+    let root = dir.path().join("failed");
+    match Executor::new().execute(&plan_running(failing, false), &root) {
+        Err(ProjectError::CommandFailed {
+            command,
+            code,
+            stderr,
+        }) => {
+            assert!(command.starts_with("sh -c for i in"));
+            assert_eq!(code, Some(3));
+            assert_eq!(stderr.lines().count(), 20);
+            assert!(stderr.starts_with("line 11\n"));
+            assert!(stderr.ends_with("line 30"));
+        }
+        other => panic!("expected CommandFailed, got {:?}", other),
+    }
+    assert!(!root.exists());
+
+    let root = dir.path().join("optional");
+    Executor::new()
+        .execute(&plan_running(failing, true), &root)
+        .unwrap();
+    assert!(root.join("main.py").is_file());
+
+    let root = dir.path().join("slow");
+    let started = Instant::now();
+    let result = Executor::new()
+        .timeout(Some(Duration::from_secs(1)))
+        .execute(&plan_running("sleep 30", false), &root);
+    assert!(
+        matches!(result, Err(ProjectError::CommandTimedOut { .. })),
+        "{:?}",
+        result
+    );
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert!(!root.exists());
+}
+
+#[test]
+fn test_conflict_policies() {
+    use poq::core::conflicts::{resolve_conflicts, ConflictPolicy, Resolution};
+    use poq::core::Plan;
+    use poq::ProjectError;
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("main.py"), "print('mine')").unwrap();
+    std::fs::write(dir.path().join("README.md"), "same").unwrap();
+    let plan = || {
+        let mut plan = Plan::new();
+        plan.write_file("main.py", "print('generated')");
+        plan.write_file("README.md", "same");
+        plan.write_file("new.py", "");
+        plan
+    };
+    let no_prompt = |_: &poq::core::conflicts::Conflict| -> Result<Resolution, ProjectError> {
+        panic!("prompt should not be called")
+    };
+
+    let mut aborted = plan();
+    match resolve_conflicts(&mut aborted, dir.path(), ConflictPolicy::Abort, no_prompt) {
+        Err(ProjectError::Conflict(paths)) => assert_eq!(paths, vec![PathBuf::from("main.py")]),
+        other => panic!("expected a conflict, got {:?}", other.map(|_| ())),
+    }
+
+    let mut skipped = plan();
+    let report =
+        resolve_conflicts(&mut skipped, dir.path(), ConflictPolicy::Skip, no_prompt).unwrap();
+    assert_eq!(report.skipped, vec![PathBuf::from("main.py")]);
+    assert_eq!(report.unchanged, vec![PathBuf::from("README.md")]);
+    let files: Vec<_> = skipped.files().map(|(p, _)| p.to_path_buf()).collect();
+    assert_eq!(files, vec![PathBuf::from("new.py")]);
+
+    let mut overwritten = plan();
+    let report = resolve_conflicts(
+        &mut overwritten,
+        dir.path(),
+        ConflictPolicy::Overwrite,
+        no_prompt,
+    )
+    .unwrap();
+    assert_eq!(report.overwritten, vec![PathBuf::from("main.py")]);
+    assert_eq!(overwritten.files().count(), 2);
+
+    let mut prompted = plan();
+    let report = resolve_conflicts(&mut prompted, dir.path(), ConflictPolicy::Prompt, |c| {
+        assert_eq!(c.existing, b"print('mine')");
+        Ok(Resolution::Skip)
+    })
+    .unwrap();
+    assert_eq!(report.skipped, vec![PathBuf::from("main.py")]);
+}
+
+#[test]
+fn test_output_dir_is_separate_from_name() {
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("elsewhere");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_poq"))
+        .args(["--no-input", "--dry-run", "-o"])
+        .arg(&target)
+        .args(["python", "base", "my-app"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&target.join("main.py").display().to_string()));
+    assert!(!target.exists());
+}
+
+#[test]
+fn test_go_projects() {
+    let dir = tempfile::tempdir().unwrap();
+    let generate = |project_type: &str, name: &str, vars: &[&str]| {
+        let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_poq"));
+        command
+            .current_dir(dir.path())
+            .env("PATH", "")
+            .arg("--no-input");
+        for var in vars {
+            command.args(["--var", var]);
+        }
+        let output = command.args(["go", project_type, name]).output().unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        dir.path().join(name)
+    };
+
+    let module = generate("module", "my-lib", &["module_path=example.com/acme/my-lib"]);
+    let go_mod = std::fs::read_to_string(module.join("go.mod")).unwrap();
+    assert!(go_mod.starts_with("module example.com/acme/my-lib\n"));
+    assert!(module.join("my_lib.go").exists());
+    assert!(module.join("my_lib_test.go").exists());
+
+    let cli = generate("cli", "tool", &["cli_framework=cobra"]);
+    let go_mod = std::fs::read_to_string(cli.join("go.mod")).unwrap();
+    assert!(go_mod.contains("github.com/spf13/cobra"));
+    let main = std::fs::read_to_string(cli.join("main.go")).unwrap();
+    assert!(main.contains("cobra.Command"));
+    assert!(!main.contains("\"flag\""));
+
+    let service = generate("service", "api", &["port=9090"]);
+    let main = std::fs::read_to_string(service.join("main.go")).unwrap();
+    assert!(main.contains("\":9090\""));
+    assert!(service.join("main_test.go").exists());
+}
+
+#[test]
+fn test_rust_projects() {
+    use poq::languages::get_language;
+    use poq::languages::rust::generators::dependency_lines;
+
+    assert_eq!(
+        dependency_lines(&["anyhow@1".to_string(), "clap@4.5+derive+env".to_string()]).unwrap(),
+        vec![
+            "anyhow = \"1\"",
+            "clap = { version = \"4.5\", features = [\"derive\", \"env\"] }"
+        ]
+    );
+    assert!(dependency_lines(&["anyhow".to_string()]).is_err());
+
+    let rust = get_language("rust").unwrap();
+    let config = Config::builtin("rust").unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let named = |project_type: &str, name: &str| -> Vec<(PathBuf, String)> {
+        let generator = rust.get_generator(&config, project_type).unwrap();
+        let project = Project::new(name, dir.path().join(name));
+        let variables = generator
+            .manifest()
+            .resolve(
+                &generator.create_context(&project, &config),
+                |_, default| Ok(default.unwrap()),
+            )
+            .unwrap();
+        let project = project.with_variables(variables);
+        generator
+            .plan(&project, &config)
+            .unwrap()
+            .files()
+            .map(|(p, c)| (p.to_path_buf(), String::from_utf8_lossy(c).into_owned()))
+            .collect()
+    };
+    let files = |project_type: &str| named(project_type, "my-tool");
+
+    let cli = files("cli");
+    let manifest = &cli
+        .iter()
+        .find(|(p, _)| p == Path::new("Cargo.toml"))
+        .unwrap()
+        .1;
+    assert!(manifest.contains("edition = \"2021\""));
+    assert!(manifest.contains("clap = { version = \"4.5\", features = [\"derive\"] }"));
+    let main = &cli
+        .iter()
+        .find(|(p, _)| p == Path::new("src/main.rs"))
+        .unwrap()
+        .1;
+    assert!(main.contains("use clap::Parser;"));
+    assert!(cli.iter().any(|(p, _)| p == Path::new("tests/cli.rs")));
+
+    let lib = files("lib");
+    assert!(lib.iter().any(|(p, _)| p == Path::new("src/lib.rs")));
+    let test = &lib
+        .iter()
+        .find(|(p, _)| p == Path::new("tests/integration.rs"))
+        .unwrap()
+        .1;
+    assert!(test.contains("use my_tool::greeting;"));
+    // Cargo only replaces `-` in crate names
+    let test = named("lib", "MyLib")
+        .into_iter()
+        .find(|(p, _)| p == Path::new("tests/integration.rs"))
+        .unwrap()
+        .1;
+    assert!(test.contains("use MyLib::greeting;"), "{}", test);
+
+    let workspace = files("workspace");
+    let root = &workspace
+        .iter()
+        .find(|(p, _)| p == Path::new("Cargo.toml"))
+        .unwrap()
+        .1;
+    assert!(root.contains("[workspace]"));
+    assert!(workspace
+        .iter()
+        .any(|(p, _)| p == Path::new("crates/my-tool-core/src/lib.rs")));
+    assert!(workspace
+        .iter()
+        .any(|(p, _)| p == Path::new("crates/my-tool/src/main.rs")));
+}
+
+#[test]
+fn test_node_projects() {
+    use poq::languages::node::generators::dependency_table;
+
+    let table = dependency_table(&["@types/node@^20".to_string(), "express@^4".to_string()]);
+    assert_eq!(
+        table.unwrap(),
+        serde_json::json!({ "@types/node": "^20", "express": "^4" })
+    );
+    assert!(dependency_table(&["express".to_string()]).is_err());
+
+    let dir = tempfile::tempdir().unwrap();
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_poq"))
+        .current_dir(dir.path())
+        .env("PATH", "")
+        .args(["--no-input", "--var", "web_framework=fastify"])
+        .args(["--var", "test_framework=jest", "node", "service", "api"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let root = dir.path().join("api");
+    let package: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(root.join("package.json")).unwrap()).unwrap();
+    assert_eq!(package["dependencies"]["fastify"], "^5.0.0");
+    assert_eq!(package["scripts"]["test"], "jest");
+    assert!(package["devDependencies"]["ts-jest"].is_string());
+    assert!(root.join("jest.config.js").exists());
+    assert!(root.join("tsconfig.json").exists());
+    assert!(std::fs::read_to_string(root.join("src/index.ts"))
+        .unwrap()
+        .contains("Fastify()"));
+    assert!(!root.join("node_modules").exists());
+
+    // A dry run previews the install without asking npm for its registry
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let bin = dir.path().join("bin");
+        std::fs::create_dir(&bin).unwrap();
+        let fake = bin.join("npm");
+        std::fs::write(
+            &fake,
+            "#!/bin/sh\necho \"$*\" >> \"$(dirname \"$0\")/calls\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&fake, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_poq"))
+            .current_dir(dir.path())
+            .env("PATH", &bin)
+            .args(["--no-input", "--dry-run", "node", "cli", "tool"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("run          npm install"), "{}", stdout);
+        assert!(!stdout.contains("Python"), "{}", stdout);
+        assert!(!bin.join("calls").exists());
+    }
+}
+
+#[test]
+fn test_jvm_projects() {
+    use poq::languages::jvm::generators::{dependency_coordinates, package_path};
+
+    assert_eq!(package_path("com.acme.app"), "com/acme/app");
+    assert!(dependency_coordinates(&["com.google.guava:guava:33.3.1-jre".to_string()]).is_ok());
+    assert!(dependency_coordinates(&["guava".to_string()]).is_err());
+
+    let dir = tempfile::tempdir().unwrap();
+    let generate = |name: &str, vars: &[&str]| {
+        let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_poq"));
+        command.current_dir(dir.path()).arg("--no-input");
+        for var in vars {
+            command.args(["--var", var]);
+        }
+        let output = command.args(["jvm", "application", name]).output().unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        dir.path().join(name)
+    };
+
+    let maven = generate("maven-app", &["group_id=com.acme.app"]);
+    let pom = std::fs::read_to_string(maven.join("pom.xml")).unwrap();
+    assert!(pom.contains("<groupId>com.acme.app</groupId>"));
+    assert!(pom.contains("<mainClass>com.acme.app.App</mainClass>"));
+    assert!(maven.join("src/main/java/com/acme/app/App.java").exists());
+    assert!(maven
+        .join("src/test/java/com/acme/app/AppTest.java")
+        .exists());
+    assert!(!maven.join("build.gradle.kts").exists());
+
+    let gradle = generate(
+        "gradle-app",
+        &[
+            "jvm_language=kotlin",
+            "build_tool=gradle",
+            "package=org.demo",
+        ],
+    );
+    let build = std::fs::read_to_string(gradle.join("build.gradle.kts")).unwrap();
+    assert!(build.contains("kotlin(\"jvm\")"));
+    assert!(build.contains("mainClass = \"org.demo.AppKt\""));
+    assert!(gradle.join("settings.gradle.kts").exists());
+    assert!(gradle.join("src/main/kotlin/org/demo/App.kt").exists());
+    assert!(!gradle.join("pom.xml").exists());
+}
+
+#[test]
+fn test_cpp_projects() {
+    let dir = tempfile::tempdir().unwrap();
+    let generate = |project_type: &str, vars: &[&str]| {
+        let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_poq"));
+        command.current_dir(dir.path()).arg("--no-input");
+        for var in vars {
+            command.args(["--var", var]);
+        }
+        let output = command
+            .args(["cpp", project_type, "geo"])
+            .arg("-o")
+            .arg(project_type)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+        dir.path().join(project_type)
+    };
+
+    let library = generate("library", &["source_language=c", "c_standard=11"]);
+    let cmake = std::fs::read_to_string(library.join("CMakeLists.txt")).unwrap();
+    assert!(cmake.contains("LANGUAGES C)"));
+    assert!(cmake.contains("set(CMAKE_C_STANDARD 11)"));
+    assert!(cmake.contains("add_test(NAME test_geo COMMAND test_geo)"));
+    assert!(library.join("include/geo/geo.h").exists());
+    assert!(library.join("src/geo.c").exists());
+    assert!(library.join(".clang-format").exists());
+    assert!(!library.join("meson.build").exists());
+    assert!(!library.join("src/main.c").exists());
+
+    let executable = generate("executable", &["meson=true"]);
+    let cmake = std::fs::read_to_string(executable.join("CMakeLists.txt")).unwrap();
+    assert!(cmake.contains("set(CMAKE_CXX_STANDARD 17)"));
+    assert!(cmake.contains("add_executable(geo src/main.cpp)"));
+    assert!(executable.join("include/geo/geo.hpp").exists());
+    assert!(executable.join("tests/test_geo.cpp").exists());
+    assert!(executable.join("meson.build").exists());
+}
+
+#[test]
+fn test_project_types_come_from_config() {
+    use poq::languages::get_language;
+
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        r#"
+        [general]
+        language = "python"
+        version = "3.12"
+
+        [project_types.api]
+        framework = "flask"
+        dependencies = ["flask"]
+
+        [test]
+        framework = "pytest"
+        "#,
+    )
+    .unwrap();
+    let config = Config::load(&config_path).unwrap();
+    let api = config.get_project_type_config("api").unwrap();
+    assert_eq!(api.framework.as_deref(), Some("flask"));
+
+    let python = get_language("python").unwrap();
+    assert_eq!(python.project_types(&config), vec!["api"]);
+    let generator = python.get_generator(&config, "api").unwrap();
+    let project = Project::new("svc", dir.path().join("svc"));
+    let plan = generator.plan(&project, &config).unwrap();
+    let requirements = |name: &str| {
+        plan.files()
+            .find(|(path, _)| *path == Path::new(name))
+            .unwrap()
+            .1
+            .to_vec()
+    };
+    assert_eq!(requirements("requirements.txt"), b"flask\n");
+    assert_eq!(
+        requirements("requirements-test.txt"),
+        b"-r requirements.txt\npytest\n"
+    );
+
+    let err = python
+        .get_generator(&Config::new().unwrap(), "clii")
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("did you mean 'cli'?"), "{}", err);
+    let err = get_language("go")
+        .unwrap()
+        .get_generator(&config, "nonsense")
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(!err.contains("did you mean"), "{}", err);
+}
+
+#[test]
+fn test_config_is_resolved_per_language() {
+    use poq::languages::{get_language, supported_languages};
+
+    for name in supported_languages() {
+        let language = get_language(name).unwrap();
+        let config = Config::builtin(name).unwrap();
+        assert_eq!(config.general.language, language.name());
+        assert!(!language.project_types(&config).is_empty(), "{}", name);
+    }
+    assert!(Config::builtin("cobol").is_err());
+
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("go.toml");
+    std::fs::write(
+        &config_path,
+        r#"
+        [general]
+        language = "go"
+        version = "1.23"
+
+        [project_types.module]
+        dependencies = ["github.com/google/uuid@v1.6.0"]
+
+        [test]
+        framework = "testing"
+        "#,
+    )
+    .unwrap();
+    let poq = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_poq"))
+            .current_dir(dir.path())
+            .env("PATH", "")
+            .arg("--no-input")
+            .arg("--config")
+            .arg(&config_path)
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    // The language comes from the config file when it isn't given
+    let answers = dir.path().join("answers.toml");
+    std::fs::write(&answers, "project_type = \"module\"\nname = \"ids\"\n").unwrap();
+    let output = poq(&["--answers", answers.to_str().unwrap()]);
+    assert!(output.status.success(), "{:?}", output);
+    let go_mod = std::fs::read_to_string(dir.path().join("ids/go.mod")).unwrap();
+    assert!(go_mod.contains("go 1.23\n"), "{}", go_mod);
+    assert!(
+        go_mod.contains("\tgithub.com/google/uuid v1.6.0\n"),
+        "{}",
+        go_mod
+    );
+
+    let output = poq(&["python", "cli", "tool"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("is for go projects, not python"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_layered_config() {
+    use poq::config::ConfigLayers;
+
+    let dir = tempfile::tempdir().unwrap();
+    let user = dir.path().join("user.toml");
+    std::fs::write(
+        &user,
+        "[variables]\nauthor = \"Org Bot\"\n\n[python.general]\nversion = \"3.12\"\n",
+    )
+    .unwrap();
+    let repo = dir.path().join("repo");
+    std::fs::create_dir_all(repo.join("src/deep")).unwrap();
+    std::fs::write(
+        repo.join(".poq.toml"),
+        "[general]\nlanguage = \"python\"\n\n[project_types.cli]\ndependencies = [\"click\"]\n",
+    )
+    .unwrap();
+    let project = poq::config::find_project_config(&repo.join("src/deep")).unwrap();
+
+    let layers = ConfigLayers::new()
+        .with_file("user", &user)
+        .unwrap()
+        .with_file("project", &project)
+        .unwrap()
+        .with_env([
+            ("POQ_TEST__FRAMEWORK".to_string(), "unittest".to_string()),
+            ("POQ_TEMPLATES_DIR".to_string(), "/ignored".to_string()),
+        ])
+        .with_overrides(&["variables.author=Jane Doe"])
+        .unwrap();
+    assert_eq!(layers.language().as_deref(), Some("python"));
+
+    let python = layers.resolve("python").unwrap();
+    assert_eq!(python.config.general.version, "3.12");
+    assert_eq!(python.config.test.framework, "unittest");
+    assert_eq!(
+        python
+            .config
+            .get_project_type_config("cli")
+            .unwrap()
+            .dependencies,
+        vec!["click"]
+    );
+    assert!(python.config.get_project_type_config("web").is_ok());
+    let origins: std::collections::BTreeMap<String, String> = python
+        .entries()
+        .into_iter()
+        .map(|(key, _, origin)| (key, origin.to_string()))
+        .collect();
+    assert!(origins["general.version"].starts_with("user ("));
+    assert!(origins["project_types.cli.dependencies"].starts_with("project ("));
+    assert_eq!(origins["test.framework"], "env (POQ_TEST__FRAMEWORK)");
+    assert_eq!(origins["variables.author"], "cli (--set variables.author)");
+    assert!(origins["general.language"].starts_with("built-in"));
+
+    // `[python.*]` tables don't leak into other languages
+    let go = layers.resolve("go").unwrap();
+    assert_eq!(go.config.general.language, "go");
+    assert_eq!(go.config.general.version, "1.22");
+    assert!(ConfigLayers::new().with_overrides(&["version"]).is_err());
+
+    // A project type defined entirely with `--set`
+    let api = ConfigLayers::new()
+        .with_overrides(&[
+            r#"project_types.api.dependencies=["flask"]"#,
+            "project_types.api.framework=flask",
+        ])
+        .unwrap()
+        .resolve("python")
+        .unwrap();
+    api.validate().unwrap();
+    let api_type = api.config.get_project_type_config("api").unwrap();
+    assert_eq!(api_type.dependencies, vec!["flask"]);
+    assert_eq!(api_type.framework.as_deref(), Some("flask"));
+}
+
+#[test]
+fn test_config_show_and_variable_defaults() {
+    use poq::cli::answers::AnswerSource;
+    use poq::templating::TemplateManifest;
+
+    let dir = tempfile::tempdir().unwrap();
+    let home = dir.path().join("home");
+    std::fs::create_dir_all(home.join(".config/poq")).unwrap();
+    std::fs::write(
+        home.join(".config/poq/config.toml"),
+        "[variables]\nauthor = \"Org Bot\"\n",
+    )
+    .unwrap();
+    let poq = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_poq"))
+            .current_dir(dir.path())
+            .env("HOME", &home)
+            .env_remove("XDG_CONFIG_HOME")
+            .env("PATH", "")
+            .env("POQ_GENERAL__VERSION", "3.11")
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let output = poq(&[
+        "config",
+        "show",
+        "--origin",
+        "--set",
+        "test.framework=unittest",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("general.version = \"3.11\"  # env (POQ_GENERAL__VERSION)\n"));
+    assert!(stdout.contains("test.framework = \"unittest\"  # cli (--set test.framework)\n"));
+    assert!(stdout.contains("variables.author = \"Org Bot\"  # user ("));
+
+    // `[variables]` replace the manifest's defaults
+    let manifest = TemplateManifest::parse(
+        "[[variables]]\nname = \"author\"\n\n[[variables]]\nname = \"year\"\ntype = \"int\"\n",
+        "poq.toml",
+    )
+    .unwrap();
+    let mut defaults = std::collections::BTreeMap::new();
+    defaults.insert("author".to_string(), toml::Value::from("Org Bot"));
+    defaults.insert("year".to_string(), toml::Value::from("2026"));
+    let answers = AnswerSource::new(false, false)
+        .with_defaults(&defaults)
+        .unwrap();
+    assert!(answers.missing(&manifest).is_empty());
+    let variables = answers
+        .resolve(&manifest, &serde_json::json!({}), &[])
+        .unwrap();
+    assert_eq!(variables["author"], "Org Bot");
+    assert_eq!(variables["year"], 2026);
+}
+
+#[test]
+fn test_config_drives_template_selection() {
+    use poq::config::ConfigLayers;
+    use poq::languages::get_language;
+
+    let python = get_language("python").unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let main_py = |overrides: &[&str]| -> Result<String, poq::ProjectError> {
+        let config = ConfigLayers::new()
+            .with_overrides(overrides)?
+            .resolve("python")?
+            .config;
+        let project_type = overrides[0].split('.').nth(1).unwrap();
+        let generator = python.get_generator(&config, project_type)?;
+        let project = Project::new("svc", dir.path().join("svc"));
+        let variables = generator
+            .manifest()
+            .resolve(
+                &generator.create_context(&project, &config),
+                |_, default| Ok(default.unwrap()),
+            )
+            .unwrap();
+        let plan = generator.plan(&project.with_variables(variables), &config)?;
+        let main = plan
+            .files()
+            .find(|(path, _)| *path == Path::new("main.py"))
+            .unwrap();
+        Ok(String::from_utf8_lossy(main.1).into_owned())
+    };
+
+    assert!(main_py(&["project_types.web.framework=fastapi"])
+        .unwrap()
+        .contains("FastAPI()"));
+    assert!(main_py(&["project_types.web.framework=flask"])
+        .unwrap()
+        .contains("Flask(__name__)"));
+    let err = main_py(&["project_types.web.framework=django"]).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Unsupported web framework 'django'"));
+
+    // A main template from another project type replaces main.py
+    let cli =
+        main_py(&["project_types.cli.main_file_template=python/data_science/main.py.template"])
+            .unwrap();
+    assert!(cli.contains("import pandas"), "{}", cli);
+    let err = main_py(&["project_types.cli.main_file_template=python/cli/missing.py.template"])
+        .unwrap_err();
+    assert!(err.to_string().contains("not found"), "{}", err);
+
+    let err = ConfigLayers::new()
+        .with_overrides(&["project_types.cli.dependancies=[\"click\"]"])
+        .unwrap()
+        .resolve("python")
+        .unwrap()
+        .validate()
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("unknown key 'dependancies' (did you mean 'dependencies'?)"),
+        "{}",
+        err
+    );
+    let typo = dir.path().join("typo.toml");
+    std::fs::write(
+        &typo,
+        "[general]\nlanguage = \"python\"\nversoin = \"3.12\"\n\n[test]\nframework = \"pytest\"\n",
+    )
+    .unwrap();
+    assert!(Config::load(&typo).is_err());
+}
+
+#[test]
+fn test_python_requirement_syntax() {
+    use poq::languages::python::requirements::{check_requirement, is_valid_version};
+
+    for version in ["3.12", "1.0.0rc1", "2!1.0.post2.dev3", "1.0+local.7"] {
+        assert!(is_valid_version(version), "{}", version);
+    }
+    for version in ["3.x", "", "1..2", "latest"] {
+        assert!(!is_valid_version(version), "{}", version);
+    }
+    for spec in [
+        "requests",
+        "requests[socks,security]>=2.31,<3",
+        "django==4.2.*",
+        "numpy (>=1.26)",
+        "tomli>=2; python_version < \"3.11\"",
+        "pkg @ https://example.com/pkg.whl",
+    ] {
+        assert!(check_requirement(spec).is_ok(), "{}", spec);
+    }
+    for spec in [
+        "",
+        "-flask",
+        "flask>=",
+        "flask>=1.0 2.0",
+        "flask=>1",
+        "pkg @ ",
+        "x;",
+    ] {
+        assert!(check_requirement(spec).is_err(), "{}", spec);
+    }
+}
+
+#[test]
+fn test_config_validation_reports_every_issue() {
+    use poq::config::{ConfigIssue, ConfigLayers};
+    use poq::languages::{get_language, supported_languages};
+    use poq::ProjectError;
+
+    for name in supported_languages() {
+        let config = Config::builtin(name).unwrap();
+        assert!(config.validate().is_ok(), "{}", name);
+    }
+    // Versions the templates would reject fail validation instead
+    let cpp = get_language("cpp").unwrap();
+    assert!(cpp.check_version("17").is_ok());
+    assert!(cpp.check_version("98").is_err());
+    let jvm = get_language("jvm").unwrap();
+    assert!(jvm.check_version("8").is_ok());
+    assert!(jvm.check_version("1.8").unwrap_err().contains("'8'"));
+
+    let dir = tempfile::tempdir().unwrap();
+    let project = dir.path().join(".poq.toml");
+    std::fs::write(
+        &project,
+        r#"[general]
+version = "3.x"
+
+[project_types.cli]
+dependencies = ["click>=8", "bad spec!"]
+main_file_template = "python/cli/missing.py.template"
+
+[test]
+framework = "nose"
+"#,
+    )
+    .unwrap();
+    let resolved = ConfigLayers::new()
+        .with_file("project", &project)
+        .unwrap()
+        .with_overrides(&["project_types.web.framework=django"])
+        .unwrap()
+        .resolve("python")
+        .unwrap();
+    let Err(ProjectError::InvalidConfig(issues)) = resolved.validate() else {
+        panic!("expected validation errors");
+    };
+    let path = project.display().to_string();
+    let located = |key: &str| -> String {
+        let issue: &ConfigIssue = issues.iter().find(|i| i.key == key).unwrap();
+        issue.location.clone().unwrap()
+    };
+    assert_eq!(issues.len(), 5, "{:?}", issues);
+    assert_eq!(located("general.version"), format!("{}:2:11", path));
+    assert_eq!(
+        located("project_types.cli.dependencies[1]"),
+        format!("{}:5:29", path)
+    );
+    assert_eq!(
+        located("project_types.cli.main_file_template"),
+        format!("{}:6:22", path)
+    );
+    assert_eq!(located("test.framework"), format!("{}:9:13", path));
+    assert_eq!(
+        located("project_types.web.framework"),
+        "cli (--set project_types.web.framework)"
+    );
+
+    // Unknown and mistyped keys are reported with the other problems
+    let typos = dir.path().join("typos.toml");
+    std::fs::write(
+        &typos,
+        r#"[general]
+verison = "3.12"
+
+[project_types.web]
+framwork = "flask"
+
+[test]
+framework = 5
+"#,
+    )
+    .unwrap();
+    let resolved = ConfigLayers::new()
+        .with_file("project", &typos)
+        .unwrap()
+        .with_overrides(&["project_types.cli.main_file_template=missing.template"])
+        .unwrap()
+        .resolve("python")
+        .unwrap();
+    let Err(ProjectError::InvalidConfig(issues)) = resolved.validate() else {
+        panic!("expected validation errors");
+    };
+    let reported: Vec<(&str, &str)> = issues
+        .iter()
+        .map(|i| (i.key.as_str(), i.location.as_deref().unwrap()))
+        .collect();
+    assert_eq!(
+        reported,
+        [
+            (
+                "general.verison",
+                format!("{}:2:11", typos.display()).as_str()
+            ),
+            (
+                "project_types.web.framwork",
+                &format!("{}:5:12", typos.display())
+            ),
+            ("test.framework", &format!("{}:8:13", typos.display())),
+            (
+                "project_types.cli.main_file_template",
+                "cli (--set project_types.cli.main_file_template)"
+            ),
+        ]
+    );
+    assert_eq!(issues[2].message, "expected a string, got 5");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_poq"))
+        .current_dir(dir.path())
+        .env("HOME", dir.path())
+        .env_remove("XDG_CONFIG_HOME")
+        .args(["config", "validate"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(&format!(
+            "{}:9:13: test.framework: unknown test framework 'nose'",
+            path
+        )),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_python_env_backends() {
+    use poq::config::ConfigLayers;
+    use poq::languages::get_language;
+    use poq::ProjectError;
+
+    let python = get_language("python").unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let project = Project::new("tool", dir.path().join("tool"));
+    let plan_for = |env: &str| {
+        let config = ConfigLayers::new()
+            .with_flag("--env", "python.settings.env", env)
+            .resolve("python")
+            .unwrap()
+            .config;
+        let plan = python
+            .get_generator(&config, "cli")
+            .unwrap()
+            .plan(&project, &config)
+            .unwrap();
+        let files: Vec<(PathBuf, String)> = plan
+            .files()
+            .map(|(path, contents)| {
+                (
+                    path.to_path_buf(),
+                    String::from_utf8_lossy(contents).into_owned(),
+                )
+            })
+            .collect();
+        let commands: Vec<String> = python
+            .setup_environment(&project, &config)
+            .unwrap()
+            .commands()
+            .map(|c| c.command_line())
+            .collect();
+        (files, commands)
+    };
+    let file = |files: &[(PathBuf, String)], name: &str| {
+        files
+            .iter()
+            .find(|(path, _)| path == Path::new(name))
+            .map(|(_, contents)| contents.clone())
+    };
+
+    let (files, commands) = plan_for("uv");
+    let pyproject = file(&files, "pyproject.toml").unwrap();
+    assert!(pyproject.contains("\"argparse\","), "{}", pyproject);
+    assert!(pyproject.contains("[dependency-groups]"));
+    assert!(toml::from_str::<toml::Table>(&pyproject).is_ok());
+    assert!(file(&files, "requirements.txt").is_none());
+    assert!(file(&files, "README.md").unwrap().contains("uv sync"));
+    assert_eq!(commands, ["uv sync"]);
+
+    let (files, commands) = plan_for("conda");
+    assert!(file(&files, "environment.yml")
+        .unwrap()
+        .contains("- python>=3.9"));
+    assert!(file(&files, "requirements.txt").is_some());
+    assert_eq!(
+        commands,
+        ["conda env create --file environment.yml --prefix .conda"]
+    );
+
+    let (files, commands) = plan_for("none");
+    assert!(file(&files, "pyproject.toml").is_none());
+    assert!(commands.is_empty());
+
+    let invalid = ConfigLayers::new()
+        .with_overrides(&["settings.env=pipenv"])
+        .unwrap()
+        .resolve("python")
+        .unwrap();
+    assert!(matches!(
+        invalid.validate(),
+        Err(ProjectError::InvalidConfig(issues)) if issues[0].key == "settings.env"
+    ));
+
+    // Typos in `[settings]` would otherwise fall back to the defaults
+    for (language, key, message) in [
+        (
+            "python",
+            "evn",
+            "unknown setting 'evn' (did you mean 'env'?)",
+        ),
+        (
+            "rust",
+            "env",
+            "unknown setting 'env'; there are no settings",
+        ),
+    ] {
+        let Err(ProjectError::InvalidConfig(issues)) = ConfigLayers::new()
+            .with_overrides(&[format!("settings.{}=uv", key)])
+            .unwrap()
+            .resolve(language)
+            .unwrap()
+            .validate()
+        else {
+            panic!("expected validation errors");
+        };
+        assert_eq!(issues[0].key, format!("settings.{}", key));
+        assert_eq!(issues[0].message, message);
+    }
+
+    // `none` runs nothing, so generation works without any tools on PATH
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_poq"))
+        .current_dir(dir.path())
+        .env("HOME", dir.path())
+        .env_remove("XDG_CONFIG_HOME")
+        .env("PATH", "")
+        .args(["--no-input", "--env", "none", "python", "base", "app"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert!(dir.path().join("app/requirements.txt").exists());
+    assert!(!dir.path().join("app/venv").exists());
+}
+
+#[test]
+fn test_python_offline_installs() {
+    use poq::config::ConfigLayers;
+    use poq::languages::get_language;
+    use poq::languages::python::env::{download_command, PackageSources};
+    use poq::languages::python::generators::common::DependencyGroups;
+
+    let python = get_language("python").unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let wheels = dir.path().join("wheels");
+    std::fs::create_dir(&wheels).unwrap();
+    let project = Project::new("tool", dir.path().join("tool"));
+    let resolve = |flags: &[(&str, &str)]| {
+        let mut layers = ConfigLayers::new();
+        for (key, value) in flags {
+            layers = layers.with_flag(key, &format!("python.settings.{}", key), value);
+        }
+        let resolved = layers.resolve("python").unwrap();
+        resolved.validate().unwrap();
+        resolved.config
+    };
+    let commands = |config: &Config| -> Vec<String> {
+        python
+            .setup_environment(&project, config)
+            .unwrap()
+            .commands()
+            .map(|c| c.command_line())
+            .collect()
+    };
+
+    let offline = resolve(&[("find_links", wheels.to_str().unwrap())]);
+    assert_eq!(
+        commands(&offline)[1],
+        format!(
+            "venv/bin/pip install -r requirements-dev.txt --no-index --find-links {}",
+            wheels.display()
+        )
+    );
+
+    let mirror = resolve(&[
+        ("env", "conda"),
+        ("index_url", "https://pypi.internal/simple"),
+    ]);
+    assert!(commands(&mirror)[0].starts_with("PIP_INDEX_URL=https://pypi.internal/simple conda "));
+
+    let poetry = resolve(&[("env", "poetry"), ("find_links", wheels.to_str().unwrap())]);
+    assert!(python.setup_environment(&project, &poetry).is_err());
+
+    let config = Config::new().unwrap();
+    let packages = DependencyGroups::from_config(&config, "web").unwrap().all();
+    assert_eq!(packages, ["fastapi", "uvicorn", "pytest"]);
+    assert_eq!(
+        download_command(
+            "python3.12",
+            &packages,
+            &PackageSources::default(),
+            Path::new("wheelhouse")
+        )
+        .command_line(),
+        "python3.12 -m pip download --dest wheelhouse fastapi uvicorn pytest"
+    );
+    let sources = PackageSources::from_config(&resolve(&[
+        ("find_links", wheels.to_str().unwrap()),
+        ("index_url", "https://pypi.internal/simple"),
+    ]))
+    .unwrap();
+    assert_eq!(
+        download_command("python", &packages[..1], &sources, &wheels).command_line(),
+        format!(
+            "python -m pip download --dest {} --index-url https://pypi.internal/simple --find-links {} fastapi",
+            wheels.display(),
+            wheels.display()
+        )
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_python_interpreter_discovery() {
+    use poq::config::ConfigLayers;
+    use poq::languages::get_language;
+    use poq::languages::python::requirements::satisfies;
+    use poq::ProjectError;
+    use std::os::unix::fs::PermissionsExt;
+
+    assert!(satisfies("3.11.4", "3.9"));
+    assert!(!satisfies("3.8.10", "3.9"));
+    assert!(satisfies("3.12.1", ">=3.10, <3.13"));
+    assert!(!satisfies("3.13.0", ">=3.10,<3.13"));
+    assert!(satisfies("3.11.9", "==3.11.*"));
+    assert!(satisfies("3.11.2", "~=3.10"));
+    assert!(!satisfies("4.0.0", "~=3.10"));
+
+    // Stands in for an interpreter answering the version query
+    let dir = tempfile::tempdir().unwrap();
+    let fake = dir.path().join("python3.11");
+    std::fs::write(&fake, "#!/bin/sh\necho 3.11.4\n").unwrap();
+    std::fs::set_permissions(&fake, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let python = get_language("python").unwrap();
+    let resolve = |version: &str| {
+        let mut config = ConfigLayers::new()
+            .with_overrides(&[
+                format!("general.version={}", version),
+                format!("settings.interpreter={}", fake.display()),
+            ])
+            .unwrap()
+            .resolve("python")
+            .unwrap()
+            .config;
+        python.resolve_toolchain(&mut config).map(|()| config)
+    };
+
+    match resolve(">=3.12") {
+        Err(ProjectError::NoInterpreter { constraint, found }) => {
+            assert_eq!(constraint, ">=3.12");
+            assert_eq!(found, [format!("{} (3.11.4)", fake.display())]);
+        }
+        other => panic!("expected NoInterpreter, got {:?}", other.map(|_| ())),
+    }
+
+    let config = resolve("3.10").unwrap();
+    let project = Project::new("tool", dir.path().join("tool"));
+    let plan = python
+        .get_generator(&config, "base")
+        .unwrap()
+        .plan(&project, &config)
+        .unwrap();
+    let readme = plan
+        .files()
+        .find(|(path, _)| *path == Path::new("README.md"))
+        .map(|(_, contents)| String::from_utf8_lossy(contents).into_owned())
+        .unwrap();
+    assert!(readme.contains("Python 3.11.4 installed"), "{}", readme);
+    let commands: Vec<String> = python
+        .setup_environment(&project, &config)
+        .unwrap()
+        .commands()
+        .map(|c| c.command_line())
+        .collect();
+    assert_eq!(commands[0], format!("{} -m venv venv", fake.display()));
+}
+
+#[test]
+fn test_python_pyproject() {
+    use poq::config::ConfigLayers;
+    use poq::languages::get_language;
+    use poq::ProjectError;
+
+    let python = get_language("python").unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let mut variables = serde_json::Map::new();
+    variables.insert("author".into(), "Jane \"JD\" Doe".into());
+    variables.insert("license".into(), "Proprietary".into());
+    let project = Project::new("tool", dir.path().join("tool")).with_variables(variables);
+    let resolve = |overrides: &[&str]| {
+        let resolved = ConfigLayers::new()
+            .with_overrides(overrides)
+            .unwrap()
+            .resolve("python")
+            .unwrap();
+        resolved.validate().map(|()| resolved.config)
+    };
+    let plan = |config: &Config| {
+        let plan = python
+            .get_generator(config, "cli")
+            .unwrap()
+            .plan(&project, config)
+            .unwrap();
+        let file = |name: &str| {
+            plan.files()
+                .find(|(path, _)| *path == Path::new(name))
+                .map(|(_, contents)| String::from_utf8_lossy(contents).into_owned())
+        };
+        (file("pyproject.toml"), file("requirements.txt"))
+    };
+
+    let config = resolve(&["settings.pyproject=true"]).unwrap();
+    let (pyproject, requirements) = plan(&config);
+    assert!(requirements.is_none());
+    let pyproject: toml::Table = toml::from_str(&pyproject.unwrap()).unwrap();
+    let table = |path: &str| -> toml::Table {
+        path.split('.')
+            .fold(&pyproject, |table, key| table[key].as_table().unwrap())
+            .clone()
+    };
+    let metadata = table("project");
+    assert_eq!(metadata["requires-python"].as_str(), Some(">=3.9"));
+    assert_eq!(metadata["license"].as_str(), Some("LicenseRef-Proprietary"));
+    assert_eq!(
+        metadata["authors"][0]["name"].as_str(),
+        Some("Jane \"JD\" Doe")
+    );
+    assert_eq!(metadata["dependencies"][0].as_str(), Some("argparse"));
+    assert_eq!(table("project.scripts")["tool"].as_str(), Some("main:main"));
+    assert_eq!(
+        table("project.optional-dependencies")["test"][0].as_str(),
+        Some("pytest")
+    );
+    assert_eq!(
+        table("build-system")["build-backend"].as_str(),
+        Some("hatchling.build")
+    );
+    let commands: Vec<String> = python
+        .setup_environment(&project, &config)
+        .unwrap()
+        .commands()
+        .map(|c| c.command_line())
+        .collect();
+    assert_eq!(commands[1], "venv/bin/pip install -e .[dev]");
+
+    let config = resolve(&["settings.pyproject=true", "settings.build_backend=flit"]).unwrap();
+    let pyproject: toml::Table = toml::from_str(&plan(&config).0.unwrap()).unwrap();
+    assert_eq!(
+        pyproject["build-system"]["build-backend"].as_str(),
+        Some("flit_core.buildapi")
+    );
+    assert_eq!(
+        pyproject["tool"]["flit"]["module"]["name"].as_str(),
+        Some("main")
+    );
+
+    let config = resolve(&["settings.pyproject=true", "settings.build_backend=none"]).unwrap();
+    let pyproject: toml::Table = toml::from_str(&plan(&config).0.unwrap()).unwrap();
+    assert!(!pyproject.contains_key("build-system"));
+
+    // Off by default
+    let (pyproject, requirements) = plan(&resolve(&[]).unwrap());
+    assert!(pyproject.is_none());
+    assert_eq!(requirements.unwrap(), "argparse\n");
+
+    let Err(ProjectError::InvalidConfig(issues)) = resolve(&[
+        "settings.build_backend=maturin",
+        "project_types.cli.entry_point=main",
+    ]) else {
+        panic!("expected validation errors");
+    };
+    let keys: Vec<&str> = issues.iter().map(|i| i.key.as_str()).collect();
+    assert_eq!(
+        keys,
+        ["project_types.cli.entry_point", "settings.build_backend"]
+    );
+}
+
+#[test]
+fn test_python_dependency_groups() {
+    use poq::config::ConfigLayers;
+    use poq::languages::get_language;
+    use poq::ProjectError;
+
+    let python = get_language("python").unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join(".poq.toml");
+    std::fs::write(
+        &config_path,
+        r#"[project_types.web]
+dependencies = ["fastapi"]
+groups.docs = ["mkdocs"]
+groups.dev = ["ruff"]
+extras.postgres = ["psycopg[binary]"]
+"#,
+    )
+    .unwrap();
+    let project = Project::new("svc", dir.path().join("svc"));
+    let resolve = |overrides: &[&str]| {
+        let resolved = ConfigLayers::new()
+            .with_file("project", &config_path)
+            .unwrap()
+            .with_overrides(overrides)
+            .unwrap()
+            .resolve("python")
+            .unwrap();
+        resolved.validate().map(|()| resolved.config)
+    };
+    let files = |config: &Config| -> Vec<(PathBuf, String)> {
+        python
+            .get_generator(config, "web")
+            .unwrap()
+            .plan(&project, config)
+            .unwrap()
+            .files()
+            .map(|(path, contents)| {
+                (
+                    path.to_path_buf(),
+                    String::from_utf8_lossy(contents).into_owned(),
+                )
+            })
+            .collect()
+    };
+    let file = |files: &[(PathBuf, String)], name: &str| {
+        files
+            .iter()
+            .find(|(path, _)| path == Path::new(name))
+            .map(|(_, contents)| contents.clone())
+    };
+
+    let plan = files(&resolve(&[]).unwrap());
+    assert_eq!(file(&plan, "requirements.txt").unwrap(), "fastapi\n");
+    assert_eq!(
+        file(&plan, "requirements-test.txt").unwrap(),
+        "-r requirements.txt\npytest\n"
+    );
+    assert_eq!(
+        file(&plan, "requirements-dev.txt").unwrap(),
+        "-r requirements.txt\nruff\nmkdocs\npytest\n"
+    );
+    assert_eq!(
+        file(&plan, "requirements-postgres.txt").unwrap(),
+        "-r requirements.txt\npsycopg[binary]\n"
+    );
+
+    let pyproject = file(
+        &files(&resolve(&["settings.env=uv"]).unwrap()),
+        "pyproject.toml",
+    );
+    let pyproject: toml::Table = toml::from_str(&pyproject.unwrap()).unwrap();
+    let groups = pyproject["dependency-groups"].as_table().unwrap();
+    assert_eq!(groups["docs"][0].as_str(), Some("mkdocs"));
+    assert_eq!(groups["test"][0].as_str(), Some("pytest"));
+    let extras = pyproject["project"]["optional-dependencies"]
+        .as_table()
+        .unwrap();
+    assert_eq!(
+        extras.keys().collect::<Vec<_>>(),
+        ["postgres"],
+        "groups aren't extras of an unpackaged project"
+    );
+
+    let Err(ProjectError::InvalidConfig(issues)) =
+        resolve(&["project_types.web.extras={ docs = [\"sphinx\"], \"my extra\" = [] }"])
+    else {
+        panic!("expected validation errors");
+    };
+    let keys: Vec<&str> = issues.iter().map(|i| i.key.as_str()).collect();
+    assert_eq!(
+        keys,
+        [
+            "project_types.web.extras.docs",
+            "project_types.web.extras.my extra"
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_python_pinning_and_constraints() {
+    use poq::config::ConfigLayers;
+    use poq::core::executor::Executor;
+    use poq::languages::get_language;
+    use poq::languages::python::requirements::{pin, requirement_name};
+    use poq::ProjectError;
+    use std::os::unix::fs::PermissionsExt;
+
+    assert_eq!(
+        requirement_name("Foo_Bar[x]>=1").as_deref(),
+        Some("foo-bar")
+    );
+    assert_eq!(
+        pin("requests [socks] >=2,<3", "2.32.3"),
+        "requests[socks]==2.32.3"
+    );
+    assert_eq!(
+        pin("tomli; python_version < '3.11'", "2.0.1"),
+        "tomli==2.0.1; python_version < '3.11'"
+    );
+    assert_eq!(
+        pin("pkg @ https://x/pkg.whl", "1.0"),
+        "pkg @ https://x/pkg.whl"
+    );
+
+    // Stands in for pip: answers the version query and the resolution report
+    let dir = tempfile::tempdir().unwrap();
+    let fake = dir.path().join("python3");
+    std::fs::write(
+        &fake,
+        r#"#!/bin/sh
+case "$*" in
+  *--report*) echo "$*" > "$(dirname "$0")/pip-args"
+    echo '{"install": [{"metadata": {"name": "FastAPI", "version": "0.115.2"}},
+      {"metadata": {"name": "uvicorn", "version": "0.32.0"}},
+      {"metadata": {"name": "pytest", "version": "8.3.3"}}]}' ;;
+  *) echo 3.12.1 ;;
+esac
+"#,
+    )
+    .unwrap();
+    std::fs::set_permissions(&fake, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let constraints = dir.path().join("constraints.txt");
+    std::fs::write(
+        &constraints,
+        "# org-wide\nstarlette<0.42\nurllib3<2  # CVE\n",
+    )
+    .unwrap();
+
+    let python = get_language("python").unwrap();
+    let resolve = |env: &str| {
+        let resolved = ConfigLayers::new()
+            .with_flag("--env", "python.settings.env", env)
+            .with_flag("--pin", "python.settings.pin", "true")
+            .with_flag(
+                "--constraints",
+                "python.settings.constraints",
+                constraints.to_str().unwrap(),
+            )
+            .with_overrides(&[format!("settings.interpreter={}", fake.display())])
+            .unwrap()
+            .resolve("python")
+            .unwrap();
+        resolved.validate().unwrap();
+        let mut config = resolved.config;
+        python
+            .resolve_dependencies(&mut config, "web", &Executor::new())
+            .unwrap();
+        config
+    };
+    let project = Project::new("svc", dir.path().join("svc"));
+    let file = |config: &Config, name: &str| {
+        python
+            .get_generator(config, "web")
+            .unwrap()
+            .plan(&project, config)
+            .unwrap()
+            .files()
+            .find(|(path, _)| *path == Path::new(name))
+            .map(|(_, contents)| String::from_utf8_lossy(contents).into_owned())
+    };
+
+    let config = resolve("venv");
+    let args = std::fs::read_to_string(dir.path().join("pip-args")).unwrap();
+    assert!(
+        args.contains(&format!(
+            "-c {} fastapi uvicorn pytest",
+            constraints.display()
+        )),
+        "{}",
+        args
+    );
+    assert_eq!(
+        file(&config, "requirements.txt").unwrap(),
+        "-c constraints.txt\nfastapi==0.115.2\nuvicorn==0.32.0\n"
+    );
+    assert_eq!(
+        file(&config, "requirements-test.txt").unwrap(),
+        "-r requirements.txt\npytest==8.3.3\n"
+    );
+    assert!(file(&config, "constraints.txt")
+        .unwrap()
+        .contains("urllib3<2"));
+
+    let pyproject = file(&resolve("uv"), "pyproject.toml").unwrap();
+    let pyproject: toml::Table = toml::from_str(&pyproject).unwrap();
+    assert_eq!(
+        pyproject["tool"]["uv"]["constraint-dependencies"][1].as_str(),
+        Some("urllib3<2")
+    );
+    assert!(matches!(
+        python.setup_environment(&project, &resolve("poetry")),
+        Err(ProjectError::Config(_))
+    ));
+
+    // pip runs like other setup commands, within `--timeout`
+    let slow = dir.path().join("slow-python");
+    std::fs::write(&slow, "#!/bin/sh\nexec sleep 30\n").unwrap();
+    std::fs::set_permissions(&slow, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let mut config = ConfigLayers::new()
+        .with_overrides(&[
+            "settings.pin=true".to_string(),
+            format!("settings.interpreter={}", slow.display()),
+        ])
+        .unwrap()
+        .resolve("python")
+        .unwrap()
+        .config;
+    let started = std::time::Instant::now();
+    let result = python.resolve_dependencies(
+        &mut config,
+        "web",
+        &Executor::new().timeout(Some(std::time::Duration::from_secs(1))),
+    );
+    assert!(
+        matches!(result, Err(ProjectError::CommandTimedOut { .. })),
+        "{:?}",
+        result
+    );
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+
+    // Neither a dry run nor the `none` environment runs pip
+    std::fs::remove_file(dir.path().join("pip-args")).unwrap();
+    resolve("none");
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_poq"))
+        .current_dir(dir.path())
+        .env("HOME", dir.path())
+        .env_remove("XDG_CONFIG_HOME")
+        .env("PATH", "")
+        .args(["--no-input", "--dry-run", "--pin", "python", "web", "app"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("aren't pinned"), "{}", stdout);
+    assert!(!dir.path().join("pip-args").exists());
+
+    std::fs::write(&constraints, "starlette<0.42\n-r other.txt\n").unwrap();
+    let Err(ProjectError::InvalidConfig(issues)) = ConfigLayers::new()
+        .with_overrides(&[format!("settings.constraints={}", constraints.display())])
+        .unwrap()
+        .resolve("python")
+        .unwrap()
+        .validate()
+    else {
+        panic!("expected validation errors");
+    };
+    assert_eq!(issues[0].key, "settings.constraints");
+    assert!(
+        issues[0].message.contains(":2: options"),
+        "{}",
+        issues[0].message
+    );
+}